@@ -0,0 +1,205 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ed25519_dalek::{Signature, VerifyingKey};
+use rocket::{
+    request::{self, FromRequest},
+    Request,
+};
+use serde::Serialize;
+use trenchesfund_common::{
+    Publisher, SignedHeaders, SignedRequest, SignerRole, SIGNED_REQUEST_CLOCK_SKEW_SECS,
+    SIGNED_REQUEST_VALIDITY_SECS,
+};
+
+use crate::{
+    decode, fail, BackendError, BackendResult, DbState, DbTransaction, IndexKey, Keyspace,
+};
+
+pub fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// Verifies the signature and validity window of a [SignedRequest] then
+/// records its nonce in `txn` so that the same request cannot be replayed
+/// once the writes it authorizes are committed.
+pub fn authenticate<T: Serialize>(
+    txn: &mut DbTransaction,
+    request: &SignedRequest<T>,
+) -> BackendResult<()> {
+    request.check_timestamp(unix_timestamp())?;
+    request.verify()?;
+
+    consume_nonce(txn, &request.nonce_key(), request.expiry())
+}
+
+/// Records a nonce until `expiry`, failing if it has already been used
+fn consume_nonce(txn: &mut DbTransaction, key: &str, expiry: u64) -> BackendResult<()> {
+    match txn.create(Keyspace::Nonces, key, &expiry.to_le_bytes()) {
+        Err(BackendError::KvAlreadyExists) => Err(BackendError::NonceReused),
+        outcome => outcome,
+    }
+}
+
+/// Removes nonces whose requests can no longer pass [SignedRequest::check_timestamp]
+pub async fn purge_expired_nonces(db: &DbState) -> BackendResult<()> {
+    let now = unix_timestamp();
+    let mut txn = db.begin().await?;
+
+    for (key, expiry) in txn.entries(Keyspace::Nonces)? {
+        let expiry = expiry
+            .try_into()
+            .map(u64::from_le_bytes)
+            .unwrap_or_default();
+
+        if expiry < now {
+            txn.delete(Keyspace::Nonces, key.as_bytes())?;
+        }
+    }
+
+    txn.commit()
+}
+
+/// Resolves the registered [Publisher] behind `address` and checks that
+/// `signer` holds at least `role` in it. Any failure is a [BackendError::PermissionDenied]
+pub fn authorize_publisher(
+    txn: &mut DbTransaction,
+    address: &str,
+    signer: &VerifyingKey,
+    role: SignerRole,
+) -> BackendResult<Publisher> {
+    let publisher_bytes = match txn.read(Keyspace::Publishers, address) {
+        Ok(bytes) => bytes,
+        Err(BackendError::KvKeyNotFound) => return Err(BackendError::PermissionDenied),
+        Err(error) => return Err(error),
+    };
+    let publisher = decode::<Publisher>(&publisher_bytes)?;
+
+    match publisher.role_of(signer) {
+        Some(held) if held >= role => Ok(publisher),
+        _ => Err(BackendError::PermissionDenied),
+    }
+}
+
+/// The address of the publisher `signer` acts for, the one registered under
+/// its own key if any, otherwise the first listing it as a signer
+pub fn publisher_of(txn: &mut DbTransaction, signer: &VerifyingKey) -> BackendResult<String> {
+    let address = Publisher::public_key_to_base58(signer);
+
+    if txn.get(Keyspace::Publishers, address.as_bytes())?.is_some() {
+        return Ok(address);
+    }
+
+    txn.index_lookup(Keyspace::Publishers, &IndexKey::Signer(address))?
+        .into_iter()
+        .next()
+        .ok_or(BackendError::PermissionDenied)
+}
+
+/// A request guard resolving the caller to the registered [Publisher] it signs
+/// for (see [publisher_of]) from the [SignedHeaders] of a request without a
+/// body. Missing or invalid credentials fail with `401 Unauthorized`, a valid
+/// signer that is not a publisher or one of its signers with `403 Forbidden`.
+///
+/// Headers signed over a body never pass it, routes that accept one take a
+/// [SignedRequest] instead.
+#[derive(Debug)]
+pub struct AuthenticatedPublisher {
+    pub publisher: Publisher,
+}
+
+impl AuthenticatedPublisher {
+    /// Verifies the signed headers against `body` and returns the signer
+    fn verify_headers(
+        txn: &mut DbTransaction,
+        request: &Request<'_>,
+        body: &[u8],
+    ) -> BackendResult<VerifyingKey> {
+        let header = |name: &str| {
+            request
+                .headers()
+                .get_one(name)
+                .ok_or(BackendError::Unauthorized)
+        };
+
+        let signer = Publisher::base58_to_public_key(header(SignedHeaders::SIGNER)?)
+            .ok_or(BackendError::InvalidPublicKey)?;
+        let timestamp = header(SignedHeaders::TIMESTAMP)?
+            .parse::<u64>()
+            .or(Err(BackendError::Unauthorized))?;
+        let body_hash = blake3::Hash::from_hex(header(SignedHeaders::BODY_HASH)?)
+            .or(Err(BackendError::Unauthorized))?;
+        if blake3::hash(body) != body_hash {
+            return Err(BackendError::InvalidSignature);
+        }
+
+        let mut signature_bytes = [0u8; 64];
+        if bs58::decode(header(SignedHeaders::SIGNATURE)?)
+            .onto(&mut signature_bytes)
+            .ok()
+            != Some(64)
+        {
+            return Err(BackendError::InvalidSignature);
+        }
+        let signature = Signature::from_bytes(&signature_bytes);
+
+        let now = unix_timestamp();
+        if timestamp > now.saturating_add(SIGNED_REQUEST_CLOCK_SKEW_SECS)
+            || now > timestamp.saturating_add(SIGNED_REQUEST_VALIDITY_SECS)
+        {
+            return Err(BackendError::RequestExpired);
+        }
+
+        let message = SignedHeaders::message(
+            request.method().as_str(),
+            &request.uri().to_string(),
+            &body_hash,
+            timestamp,
+        );
+        signer
+            .verify_strict(&message, &signature)
+            .or(Err(BackendError::InvalidSignature))?;
+
+        let nonce_key = Publisher::public_key_to_base58(&signer)
+            + ":"
+            + blake3::hash(&signature_bytes).to_hex().as_str();
+        consume_nonce(
+            txn,
+            &nonce_key,
+            timestamp.saturating_add(SIGNED_REQUEST_VALIDITY_SECS),
+        )?;
+
+        Ok(signer)
+    }
+
+    /// Consumes the nonce of the headers only if the signer is a publisher
+    async fn authorize(db: &DbState, request: &Request<'_>, body: &[u8]) -> BackendResult<Self> {
+        let mut txn = db.begin().await?;
+
+        let signer = Self::verify_headers(&mut txn, request, body)?;
+        let address = publisher_of(&mut txn, &signer)?;
+        let publisher = authorize_publisher(&mut txn, &address, &signer, SignerRole::Editor)?;
+        txn.commit()?;
+
+        Ok(Self { publisher })
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AuthenticatedPublisher {
+    type Error = BackendError;
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let db = match DbState::managed(request) {
+            Ok(db) => db,
+            Err(error) => return fail(request, error),
+        };
+
+        match Self::authorize(db, request, &[]).await {
+            Ok(caller) => request::Outcome::Success(caller),
+            Err(error) => fail(request, error),
+        }
+    }
+}
@@ -0,0 +1,422 @@
+//! Operator tool to seed, inspect and repair the `PROJECTS` and `PUBLISHERS` keyspaces.
+//! It opens the store directly, so stop the server before running it.
+
+use std::{collections::HashSet, error::Error, fs, process::ExitCode, str::FromStr};
+
+use trenchesfund_backend::*;
+use trenchesfund_common::{Project, Publisher};
+
+const USAGE: &str = "Usage: trenchesfund-admin <COMMAND>
+
+Commands:
+  list <projects|publishers>                  List keys, schema versions and decode status
+  show <projects|publishers> <KEY>            Print a record as JSON
+  import <projects|publishers> <FILE>         Validate and insert the records in a JSON array
+  export <projects|publishers> [FILE]         Write every readable record as a JSON array
+  delete <projects|publishers> <KEY>          Delete a record
+  rekey <projects|publishers> <KEY> <NEW_KEY> Move a record to its own key, for projects the id
+                                              derived from their publisher and name and for
+                                              publishers their address
+  validate [projects|publishers]              Check every record decodes and is consistent
+  migrate                                     Upgrade records to the current schema version";
+
+type AdminResult<T> = Result<T, Box<dyn Error>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dataset {
+    Projects,
+    Publishers,
+}
+
+impl Dataset {
+    fn keyspace(&self) -> Keyspace {
+        match self {
+            Self::Projects => Keyspace::Projects,
+            Self::Publishers => Keyspace::Publishers,
+        }
+    }
+
+    /// Decodes `bytes` and checks that the record is stored under the right key
+    fn check(&self, key: &str, bytes: &[u8]) -> Result<(), String> {
+        match self {
+            Self::Projects => {
+                let project = decode::<Project>(bytes).map_err(|error| error.to_string())?;
+
+                if project.id_base58() != key {
+                    return Err(format!(
+                        "key does not match project id `{}`",
+                        project.id_base58()
+                    ));
+                }
+            }
+            Self::Publishers => {
+                let publisher = decode::<Publisher>(bytes).map_err(|error| error.to_string())?;
+
+                if publisher.address() != key {
+                    return Err(format!(
+                        "key does not match publisher address `{}`",
+                        publisher.address()
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Dataset {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "projects" => Ok(Self::Projects),
+            "publishers" => Ok(Self::Publishers),
+            _ => Err(format!(
+                "Unknown dataset `{value}`, expected `projects` or `publishers`"
+            )),
+        }
+    }
+}
+
+enum Command<'a> {
+    List(Dataset),
+    Show(Dataset, &'a str),
+    Import(Dataset, &'a str),
+    Export(Dataset, Option<&'a str>),
+    Delete(Dataset, &'a str),
+    Rekey(Dataset, &'a str, &'a str),
+    Validate(Vec<Dataset>),
+    Migrate,
+}
+
+impl<'a> Command<'a> {
+    fn parse(args: &'a [String]) -> AdminResult<Option<Self>> {
+        let args = args.iter().map(String::as_str).collect::<Vec<&str>>();
+
+        let command = match args.as_slice() {
+            ["list", dataset] => Self::List(dataset.parse()?),
+            ["show", dataset, key] => Self::Show(dataset.parse()?, key),
+            ["import", dataset, path] => Self::Import(dataset.parse()?, path),
+            ["export", dataset] => Self::Export(dataset.parse()?, None),
+            ["export", dataset, path] => Self::Export(dataset.parse()?, Some(path)),
+            ["delete", dataset, key] => Self::Delete(dataset.parse()?, key),
+            ["rekey", dataset, old_key, new_key] => Self::Rekey(dataset.parse()?, old_key, new_key),
+            ["validate"] => Self::Validate(vec![Dataset::Publishers, Dataset::Projects]),
+            ["validate", dataset] => Self::Validate(vec![dataset.parse()?]),
+            ["migrate"] => Self::Migrate,
+            _ => return Ok(None),
+        };
+
+        Ok(Some(command))
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+
+    match run(&args).await {
+        Ok(code) => code,
+        Err(error) => {
+            eprintln!("error: {error}");
+
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(args: &[String]) -> AdminResult<ExitCode> {
+    let Some(command) = Command::parse(args)? else {
+        eprintln!("{USAGE}");
+
+        return Ok(ExitCode::from(2));
+    };
+
+    let db = DbState::open(&DatabaseConfig::load()?).await?;
+
+    match command {
+        Command::List(dataset) => list(&db, dataset).await,
+        Command::Show(dataset, key) => show(&db, dataset, key).await,
+        Command::Import(dataset, path) => import(&db, dataset, path).await,
+        Command::Export(dataset, path) => export(&db, dataset, path).await,
+        Command::Delete(dataset, key) => delete(&db, dataset, key).await,
+        Command::Rekey(dataset, old_key, new_key) => rekey(&db, dataset, old_key, new_key).await,
+        Command::Validate(datasets) => validate(&db, &datasets).await,
+        Command::Migrate => migrate(&db).await,
+    }
+}
+
+async fn list(db: &DbState, dataset: Dataset) -> AdminResult<ExitCode> {
+    for (key, bytes) in db.entries(dataset.keyspace()).await? {
+        let status = match dataset.check(&key, &bytes) {
+            Ok(()) => "ok".to_string(),
+            Err(error) => error,
+        };
+
+        println!("{key}\tv{}\t{status}", stored_version(&bytes));
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+async fn show(db: &DbState, dataset: Dataset, key: &str) -> AdminResult<ExitCode> {
+    let bytes = db.read(dataset.keyspace(), key).await?;
+
+    let json = match dataset {
+        Dataset::Projects => serde_json::to_string_pretty(&decode::<Project>(&bytes)?)?,
+        Dataset::Publishers => serde_json::to_string_pretty(&decode::<Publisher>(&bytes)?)?,
+    };
+    println!("{json}");
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Stores each record the way the API does, so it is validated, given its
+/// derived id and history and indexed for search. Invalid records are skipped.
+async fn import(db: &DbState, dataset: Dataset, path: &str) -> AdminResult<ExitCode> {
+    let json = fs::read_to_string(path)?;
+    let mut outcomes = Vec::<(String, BackendResult<String>)>::new();
+
+    match dataset {
+        Dataset::Projects => {
+            for project in serde_json::from_str::<Vec<Project>>(&json)? {
+                let name = project.name.clone();
+                let mut txn = db.begin().await?;
+
+                let outcome =
+                    import_project(&mut txn, project).and_then(|key| txn.commit().map(|()| key));
+                outcomes.push((name, outcome));
+            }
+        }
+        Dataset::Publishers => {
+            for publisher in serde_json::from_str::<Vec<Publisher>>(&json)? {
+                let address = publisher.address();
+                let mut txn = db.begin().await?;
+
+                let outcome = insert_publisher(&mut txn, &publisher, unix_timestamp())
+                    .and_then(|address| txn.commit().map(|()| address));
+                outcomes.push((address, outcome));
+            }
+        }
+    }
+
+    let mut failed = false;
+    for (name, outcome) in outcomes {
+        match outcome {
+            Ok(key) => println!("imported {key}"),
+            Err(error) => {
+                failed = true;
+                eprintln!("skipped {name}: {error}");
+            }
+        }
+    }
+
+    Ok(exit_code(failed))
+}
+
+/// Lists `project` under the stored publisher it names
+fn import_project(txn: &mut DbTransaction, project: Project) -> BackendResult<String> {
+    let publisher =
+        decode::<Publisher>(&txn.read(Keyspace::Publishers, project.publisher_address())?)?;
+
+    insert_project(txn, project, publisher)
+}
+
+async fn export(db: &DbState, dataset: Dataset, path: Option<&str>) -> AdminResult<ExitCode> {
+    let entries = db.entries(dataset.keyspace()).await?;
+    let mut failed = false;
+
+    let json = match dataset {
+        Dataset::Projects => {
+            let mut projects = Vec::<Project>::new();
+
+            for (key, bytes) in entries {
+                match decode::<Project>(&bytes) {
+                    Ok(project) => projects.push(project),
+                    Err(error) => {
+                        failed = true;
+                        eprintln!("skipped {key}: {error}");
+                    }
+                }
+            }
+
+            serde_json::to_string_pretty(&projects)?
+        }
+        Dataset::Publishers => {
+            let mut publishers = Vec::<Publisher>::new();
+
+            for (key, bytes) in entries {
+                match decode::<Publisher>(&bytes) {
+                    Ok(publisher) => publishers.push(publisher),
+                    Err(error) => {
+                        failed = true;
+                        eprintln!("skipped {key}: {error}");
+                    }
+                }
+            }
+
+            serde_json::to_string_pretty(&publishers)?
+        }
+    };
+
+    match path {
+        Some(path) => fs::write(path, json)?,
+        None => println!("{json}"),
+    }
+
+    Ok(exit_code(failed))
+}
+
+async fn delete(db: &DbState, dataset: Dataset, key: &str) -> AdminResult<ExitCode> {
+    match dataset {
+        Dataset::Projects => {
+            if let Err(error) = db.remove_indexed::<Project>(Keyspace::Projects, key).await {
+                if error == BackendError::KvKeyNotFound {
+                    return Err(error.into());
+                }
+
+                // The record cannot be decoded so its index entries are unknown
+                db.remove(Keyspace::Projects, key).await?;
+                db.rebuild_indexes::<Project>(Keyspace::Projects).await?;
+            }
+        }
+        Dataset::Publishers => {
+            if let Err(error) = db
+                .remove_indexed::<Publisher>(Keyspace::Publishers, key)
+                .await
+            {
+                if error == BackendError::KvKeyNotFound {
+                    return Err(error.into());
+                }
+
+                db.remove(Keyspace::Publishers, key).await?;
+                db.rebuild_indexes::<Publisher>(Keyspace::Publishers)
+                    .await?;
+            }
+        }
+    }
+
+    rebuild_search_index(db).await?;
+    println!("deleted {key}");
+
+    Ok(ExitCode::SUCCESS)
+}
+
+async fn rekey(
+    db: &DbState,
+    dataset: Dataset,
+    old_key: &str,
+    new_key: &str,
+) -> AdminResult<ExitCode> {
+    let bytes = db.read(dataset.keyspace(), old_key).await?;
+
+    match dataset {
+        Dataset::Projects => {
+            // Projects are stored under the id derived from their publisher and
+            // name, so the only key a project can move to is that id
+            let mut project = decode::<Project>(&bytes)?;
+            let id = Project::derive_id(project.publisher_address(), &project.slug());
+
+            if Project::parse_id(new_key) != Some(id) {
+                return Err(format!(
+                    "`{new_key}` is not the project id `{}` derived from its publisher and name",
+                    Project::id_to_base58(&id)
+                )
+                .into());
+            }
+            project.id = id;
+
+            db.rekey_indexed(Keyspace::Projects, old_key, &project.id_base58(), &project)
+                .await?;
+        }
+        Dataset::Publishers => {
+            // Publishers are found, redirected and authorized by their address,
+            // so the only key a publisher can move to is its own address
+            let publisher = decode::<Publisher>(&bytes)?;
+
+            if publisher.address() != new_key {
+                return Err(format!(
+                    "`{new_key}` is not the publisher address `{}`, rotate its key instead",
+                    publisher.address()
+                )
+                .into());
+            }
+
+            let mut txn = db.begin().await?;
+            move_publisher(&mut txn, old_key, &publisher)?;
+            txn.commit()?;
+        }
+    }
+
+    rebuild_search_index(db).await?;
+    println!("moved {old_key} to {new_key}");
+
+    Ok(ExitCode::SUCCESS)
+}
+
+async fn validate(db: &DbState, datasets: &[Dataset]) -> AdminResult<ExitCode> {
+    let publishers = db
+        .entries(Keyspace::Publishers)
+        .await?
+        .into_iter()
+        .map(|(key, _)| key)
+        .collect::<HashSet<String>>();
+    let mut failed = false;
+
+    for dataset in datasets {
+        for (key, bytes) in db.entries(dataset.keyspace()).await? {
+            let mut problems = Vec::<String>::new();
+
+            if let Err(error) = dataset.check(&key, &bytes) {
+                problems.push(error);
+            }
+
+            if let (Dataset::Projects, Ok(project)) = (dataset, decode::<Project>(&bytes)) {
+                if !publishers.contains(project.publisher_address()) {
+                    problems.push(format!(
+                        "publisher `{}` does not exist",
+                        project.publisher_address()
+                    ));
+                }
+            }
+
+            for problem in problems {
+                failed = true;
+                eprintln!("{}/{key}: {problem}", dataset.keyspace());
+            }
+        }
+    }
+
+    if !failed {
+        println!("all records are valid");
+    }
+
+    Ok(exit_code(failed))
+}
+
+async fn migrate(db: &DbState) -> AdminResult<ExitCode> {
+    let mut failed = false;
+
+    for (keyspace, report) in migrate_all(db).await? {
+        println!("{keyspace}: migrated {} records", report.migrated);
+
+        for key in report.failed {
+            failed = true;
+            eprintln!("{keyspace}/{key}: unable to migrate");
+        }
+    }
+
+    // Migrated projects may have moved to a new key
+    rebuild_search_index(db).await?;
+
+    Ok(exit_code(failed))
+}
+
+fn exit_code(failed: bool) -> ExitCode {
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
@@ -0,0 +1,253 @@
+use std::{fs, path::PathBuf};
+
+use rocket::figment::Figment;
+use serde::Deserialize;
+use surrealkv::{Durability, Options};
+
+use crate::{BackendError, BackendResult, Keyspace};
+
+/// The key under which [DatabaseConfig] is read from `Rocket.toml`, e.g.
+/// `[default.database]`, or from the `ROCKET_DATABASE` environment variable
+pub const DATABASE_CONFIG_KEY: &str = "database";
+
+/// The key under which [SolanaConfig] is read, e.g. `[default.solana]`
+pub const SOLANA_CONFIG_KEY: &str = "solana";
+
+/// File written and removed at startup to check that the data directory is writable
+const WRITE_PROBE: &str = ".write-probe";
+
+/// Where and how the KV store is opened
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct DatabaseConfig {
+    /// Directory holding the store, relative paths are resolved against the
+    /// working directory
+    pub dir: PathBuf,
+    /// Sub-directory of [DatabaseConfig::dir] holding every keyspace
+    pub store: String,
+    pub sync: SyncMode,
+    /// Values larger than this many bytes are read back from the log files
+    /// instead of being kept in memory
+    pub max_value_threshold: usize,
+    /// Size at which a log segment is closed and a new one started
+    pub max_segment_size: u64,
+    /// Upper bound on the segments merged by a single compaction
+    pub max_compaction_segment_size: u64,
+    /// Sub-directories of the per-dataset stores written by earlier releases,
+    /// imported into [DatabaseConfig::store] at startup
+    pub stores: StoreNames,
+}
+
+impl DatabaseConfig {
+    /// Reads the `database` section of the Rocket configuration, falling back
+    /// to the defaults for anything missing
+    pub fn from_figment(figment: &Figment) -> BackendResult<Self> {
+        if !figment.contains(DATABASE_CONFIG_KEY) {
+            return Ok(Self::default());
+        }
+
+        figment
+            .extract_inner(DATABASE_CONFIG_KEY)
+            .map_err(|error| BackendError::Config(error.to_string()))
+    }
+
+    /// The configuration Rocket would load, from `Rocket.toml` and `ROCKET_*` variables
+    pub fn load() -> BackendResult<Self> {
+        Self::from_figment(&rocket::Config::figment())
+    }
+
+    pub fn store_dir(&self, store_name: &str) -> PathBuf {
+        self.dir.join(store_name)
+    }
+
+    pub fn store_options(&self, store_name: &str) -> Options {
+        let mut opts = Options::new();
+        opts.dir = self.store_dir(store_name);
+        opts.max_value_threshold = self.max_value_threshold;
+        opts.max_segment_size = self.max_segment_size;
+        opts.max_compaction_segment_size = self.max_compaction_segment_size;
+
+        opts
+    }
+
+    /// Creates the data directory if needed and fails if a file cannot be written to it
+    pub fn ensure_writable(&self) -> BackendResult<()> {
+        let unwritable = |error: std::io::Error| {
+            BackendError::DatabaseDirUnwritable(self.dir.display().to_string(), error.to_string())
+        };
+
+        fs::create_dir_all(&self.dir).map_err(unwritable)?;
+
+        let probe = self.dir.join(WRITE_PROBE);
+        fs::write(&probe, []).map_err(unwritable)?;
+        fs::remove_file(&probe).map_err(unwritable)?;
+
+        Ok(())
+    }
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        let defaults = Options::new();
+
+        Self {
+            dir: PathBuf::from("DATABASE"),
+            store: "STORE".to_string(),
+            sync: SyncMode::default(),
+            max_value_threshold: defaults.max_value_threshold,
+            max_segment_size: defaults.max_segment_size,
+            max_compaction_segment_size: defaults.max_compaction_segment_size,
+            stores: StoreNames::default(),
+        }
+    }
+}
+
+/// The Solana RPC endpoint watched for payments of transfer requests
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct SolanaConfig {
+    pub rpc_url: String,
+    /// Seconds between two checks of the pending transfer requests, at least 1
+    pub poll_interval_secs: u64,
+    /// Seconds after which an unpaid transfer request is dropped
+    pub request_validity_secs: u64,
+}
+
+impl SolanaConfig {
+    /// Reads the `solana` section of the Rocket configuration, falling back
+    /// to the defaults for anything missing
+    pub fn from_figment(figment: &Figment) -> BackendResult<Self> {
+        if !figment.contains(SOLANA_CONFIG_KEY) {
+            return Ok(Self::default());
+        }
+
+        let config = figment
+            .extract_inner::<Self>(SOLANA_CONFIG_KEY)
+            .map_err(|error| BackendError::Config(error.to_string()))?;
+
+        // A zero period would make the interval of the transfer watcher panic
+        if config.poll_interval_secs == 0 {
+            return Err(BackendError::Config(
+                "`solana.poll_interval_secs` must be at least 1".to_string(),
+            ));
+        }
+
+        Ok(config)
+    }
+}
+
+impl Default for SolanaConfig {
+    fn default() -> Self {
+        Self {
+            rpc_url: "https://api.mainnet-beta.solana.com".to_string(),
+            poll_interval_secs: 10,
+            request_validity_secs: 60 * 60,
+        }
+    }
+}
+
+/// When committed writes are flushed to disk
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncMode {
+    /// Leave flushing to the operating system
+    #[default]
+    Eventual,
+    /// Flush before every commit returns
+    Immediate,
+}
+
+impl From<SyncMode> for Durability {
+    fn from(value: SyncMode) -> Self {
+        match value {
+            SyncMode::Eventual => Durability::Eventual,
+            SyncMode::Immediate => Durability::Immediate,
+        }
+    }
+}
+
+/// The directory name of each legacy store inside [DatabaseConfig::dir]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct StoreNames {
+    pub publishers: String,
+    pub projects: String,
+    pub nonces: String,
+    pub sessions: String,
+    pub search: String,
+}
+
+impl Default for StoreNames {
+    fn default() -> Self {
+        Self {
+            publishers: "PUBLISHERS".to_string(),
+            projects: "PROJECTS".to_string(),
+            nonces: "NONCES".to_string(),
+            sessions: "SESSIONS".to_string(),
+            search: "SEARCH".to_string(),
+        }
+    }
+}
+
+impl StoreNames {
+    /// Each store paired with the keyspace it is imported into
+    pub fn by_keyspace(&self) -> [(Keyspace, &str); 5] {
+        [
+            (Keyspace::Publishers, &self.publishers),
+            (Keyspace::Projects, &self.projects),
+            (Keyspace::Nonces, &self.nonces),
+            (Keyspace::Sessions, &self.sessions),
+            (Keyspace::Search, &self.search),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rocket::figment::providers::{Format, Toml};
+
+    use super::*;
+
+    #[test]
+    fn builds_store_options_from_the_config() {
+        let figment = Figment::from(Toml::string(
+            r#"
+            [database]
+            dir = "/var/lib/trenchesfund"
+            max_value_threshold = 4096
+            max_segment_size = 1024
+            "#,
+        ));
+        let config = DatabaseConfig::from_figment(&figment).unwrap();
+        let options = config.store_options(&config.store);
+
+        assert_eq!(options.dir, PathBuf::from("/var/lib/trenchesfund/STORE"));
+        assert_eq!(options.max_value_threshold, 4096);
+        assert_eq!(options.max_segment_size, 1024);
+        assert_eq!(
+            options.max_compaction_segment_size,
+            Options::new().max_compaction_segment_size
+        );
+    }
+
+    #[test]
+    fn rejects_a_zero_poll_interval() {
+        let config = |interval: u64| {
+            SolanaConfig::from_figment(&Figment::from(Toml::string(&format!(
+                "[solana]\npoll_interval_secs = {interval}"
+            ))))
+        };
+
+        assert!(matches!(config(0), Err(BackendError::Config(_))));
+        assert_eq!(config(5).unwrap().poll_interval_secs, 5);
+    }
+
+    #[test]
+    fn defaults_match_the_store_defaults() {
+        let options = DatabaseConfig::default().store_options("STORE");
+        let defaults = Options::new();
+
+        assert_eq!(options.max_value_threshold, defaults.max_value_threshold);
+        assert_eq!(options.max_segment_size, defaults.max_segment_size);
+    }
+}
@@ -1,131 +1,231 @@
 use core::fmt;
-use std::path::PathBuf;
+use std::sync::Arc;
 
-use async_dup::Arc;
-use async_lock::RwLock;
-use surrealkv::{Options, Store};
+use rocket::Request;
 
-use crate::{BackendError, BackendResult, KV, PROJECTS_DB, PUBLISHERS_DB};
+use crate::{
+    decode, encode, stored_version, Access, BackendError, BackendResult, DatabaseConfig,
+    DbTransaction, IndexKey, Indexed, Keyspace, MemoryStorage, MigrationReport, Storage,
+    SurrealKvStorage, Versioned, INDEX_PREFIX,
+};
 
+/// Handle to the storage backend, managed by Rocket and cheap to clone
+#[derive(Clone)]
 pub struct DbState {
-    publishers: Arc<RwLock<Store>>,
-    projects: Arc<RwLock<Store>>,
+    storage: Arc<dyn Storage>,
 }
 
 impl DbState {
-    pub async fn init() -> BackendResult<()> {
-        let new_self = Self {
-            publishers: Arc::new(RwLock::new(Self::store_ops(PUBLISHERS_DB)?)),
-            projects: Arc::new(RwLock::new(Self::store_ops(PROJECTS_DB)?)),
-        };
+    pub fn new(storage: impl Storage + 'static) -> Self {
+        Self {
+            storage: Arc::new(storage),
+        }
+    }
 
-        KV.set(new_self)
-            .await
-            .or(Err(BackendError::KvAlreadyInitialized))?;
+    /// Opens the surrealkv store described by `config`
+    pub async fn open(config: &DatabaseConfig) -> BackendResult<Self> {
+        Ok(Self::new(SurrealKvStorage::open(config).await?))
+    }
 
-        Ok(())
+    /// An empty store that lives as long as the returned handle
+    pub fn in_memory() -> Self {
+        Self::new(MemoryStorage::new())
     }
 
-    fn store_ops(db_name: &str) -> BackendResult<Store> {
-        let mut path = PathBuf::new();
-        path.push(env!("CARGO_MANIFEST_DIR"));
-        path.pop();
-        path.push("DATABASE");
-        path.push(db_name);
+    /// The instance managed by the Rocket serving `request`, for request guards
+    pub fn managed<'r>(request: &'r Request<'_>) -> BackendResult<&'r Self> {
+        request
+            .rocket()
+            .state::<Self>()
+            .ok_or(BackendError::KvUninitialized)
+    }
 
-        let mut opts = Options::new();
-        opts.dir = path;
+    /// Starts a transaction for related reads and writes across keyspaces
+    pub async fn begin(&self) -> BackendResult<DbTransaction> {
+        Ok(DbTransaction::new(self.storage.begin(Access::Write).await?))
+    }
 
-        Ok(Store::new(opts)?)
+    /// Starts a transaction that will only read
+    pub async fn begin_read(&self) -> BackendResult<DbTransaction> {
+        Ok(DbTransaction::new(self.storage.begin(Access::Read).await?))
     }
 
-    async fn set_key_value(db_name: &str, key: &str, value: &[u8]) -> BackendResult<()> {
-        let mut txn = Self::get_store()?.get_db(db_name).write().await.begin()?;
-        let key = key.as_bytes();
+    pub async fn create(&self, keyspace: Keyspace, key: &str, value: &[u8]) -> BackendResult<()> {
+        let mut txn = self.begin().await?;
+        txn.create(keyspace, key, value)?;
 
-        txn.set(key, value)?;
-        txn.commit()?;
+        txn.commit()
+    }
+
+    pub async fn update(&self, keyspace: Keyspace, key: &str, value: &[u8]) -> BackendResult<()> {
+        let mut txn = self.begin().await?;
+        txn.update(keyspace, key, value)?;
 
-        Ok(())
+        txn.commit()
     }
 
-    pub async fn create(db_name: &str, key: &str, value: &[u8]) -> BackendResult<()> {
-        if let Err(error) = Self::read(db_name, key).await {
-            match error {
-                BackendError::KvKeyNotFound => Self::set_key_value(db_name, key, value).await,
-                _ => Err(error),
-            }
-        } else {
-            Err(BackendError::KvAlreadyExists)
-        }
+    /// Deletes `key` whether or not it exists
+    pub async fn purge(&self, keyspace: Keyspace, key: &str) -> BackendResult<()> {
+        let mut txn = self.begin().await?;
+        txn.delete(keyspace, key.as_bytes())?;
+
+        txn.commit()
     }
 
-    pub async fn update(db_name: &str, key: &str, value: &[u8]) -> BackendResult<()> {
-        Self::read(db_name, key).await?;
-        Self::set_key_value(db_name, key, value).await
+    pub async fn remove(&self, keyspace: Keyspace, key: &str) -> BackendResult<()> {
+        let mut txn = self.begin().await?;
+        txn.remove(keyspace, key)?;
+
+        txn.commit()
     }
 
-    pub async fn purge(db_name: &str, key: &str) -> BackendResult<()> {
-        let mut txn = Self::get_store()?.get_db(db_name).write().await.begin()?;
-        let key = key.as_bytes();
+    pub async fn read(&self, keyspace: Keyspace, key: &str) -> BackendResult<Vec<u8>> {
+        self.begin_read().await?.read(keyspace, key)
+    }
 
-        txn.delete(key)?;
-        txn.commit()?;
+    pub async fn values(&self, keyspace: Keyspace) -> BackendResult<Vec<Vec<u8>>> {
+        let results = self
+            .entries(keyspace)
+            .await?
+            .into_iter()
+            .map(|(_, value)| value)
+            .collect();
 
-        Ok(())
+        Ok(results)
     }
 
-    pub async fn remove(db_name: &str, key: &str) -> BackendResult<()> {
-        Self::read(db_name, key).await?;
+    pub async fn entries(&self, keyspace: Keyspace) -> BackendResult<Vec<(String, Vec<u8>)>> {
+        self.begin_read().await?.entries(keyspace)
+    }
 
-        let mut txn = Self::get_store()?.get_db(db_name).write().await.begin()?;
-        let key = key.as_bytes();
+    /// Inserts a record and its index entries in one transaction
+    pub async fn create_indexed<T: Versioned + Indexed>(
+        &self,
+        keyspace: Keyspace,
+        key: &str,
+        record: &T,
+    ) -> BackendResult<()> {
+        let mut txn = self.begin().await?;
+        txn.create_indexed(keyspace, key, record)?;
+
+        txn.commit()
+    }
 
-        txn.delete(key)?;
-        txn.commit()?;
+    /// Replaces a record and moves its index entries in one transaction
+    pub async fn update_indexed<T: Versioned + Indexed>(
+        &self,
+        keyspace: Keyspace,
+        key: &str,
+        record: &T,
+    ) -> BackendResult<()> {
+        let mut txn = self.begin().await?;
+        txn.update_indexed(keyspace, key, record)?;
+
+        txn.commit()
+    }
 
-        Ok(())
-    }
-
-    pub async fn read(db_name: &str, key: &str) -> BackendResult<Vec<u8>> {
-        Self::get_store()?
-            .get_db(db_name)
-            .read()
-            .await
-            .begin()?
-            .get(key.as_bytes())?
-            .ok_or(BackendError::KvKeyNotFound)
-    }
-
-    pub async fn values(db_name: &str) -> BackendResult<Vec<Vec<u8>>> {
-        let results = Self::get_store()?
-            .get_db(db_name)
-            .read()
-            .await
-            .begin()?
-            .scan(.., None)
-            .map(|value| {
-                if let Ok((_, two, _)) = value {
-                    two
-                } else {
-                    Vec::default()
-                }
-            })
-            .collect();
+    /// Removes a record and its index entries in one transaction
+    pub async fn remove_indexed<T: Versioned + Indexed>(
+        &self,
+        keyspace: Keyspace,
+        key: &str,
+    ) -> BackendResult<()> {
+        let mut txn = self.begin().await?;
+        txn.remove_indexed::<T>(keyspace, key)?;
 
-        Ok(results)
+        txn.commit()
+    }
+
+    /// Moves a record to `new_key` in one transaction, writing `value` in its place
+    pub async fn rekey(
+        &self,
+        keyspace: Keyspace,
+        old_key: &str,
+        new_key: &str,
+        value: &[u8],
+    ) -> BackendResult<()> {
+        let mut txn = self.begin().await?;
+        txn.rekey(keyspace, old_key, new_key, value)?;
+
+        txn.commit()
+    }
+
+    /// Moves a record and its index entries to `new_key` in one transaction
+    pub async fn rekey_indexed<T: Versioned + Indexed>(
+        &self,
+        keyspace: Keyspace,
+        old_key: &str,
+        new_key: &str,
+        record: &T,
+    ) -> BackendResult<()> {
+        let mut txn = self.begin().await?;
+        txn.rekey_indexed(keyspace, old_key, new_key, record)?;
+
+        txn.commit()
+    }
+
+    /// The sorted keys of the records indexed under `index_key`
+    pub async fn index_lookup(
+        &self,
+        keyspace: Keyspace,
+        index_key: &IndexKey,
+    ) -> BackendResult<Vec<String>> {
+        self.begin_read().await?.index_lookup(keyspace, index_key)
     }
 
-    pub fn get_db(&self, db_name: &str) -> Arc<RwLock<Store>> {
-        if db_name.as_bytes() == b"PUBLISHERS" {
-            self.publishers.clone()
-        } else {
-            self.projects.clone()
+    /// Drops every index entry and recomputes them from the stored records.
+    /// Records that cannot be decoded are left unindexed.
+    pub async fn rebuild_indexes<T: Versioned + Indexed>(
+        &self,
+        keyspace: Keyspace,
+    ) -> BackendResult<()> {
+        let mut txn = self.begin().await?;
+
+        let stale_entries = txn.scan_prefix(keyspace, &[INDEX_PREFIX])?;
+        let records = txn.entries(keyspace)?;
+
+        for (entry, _) in stale_entries {
+            txn.delete(keyspace, &entry)?;
         }
+
+        for (key, value) in records {
+            if let Ok(record) = decode::<T>(&value) {
+                for index_key in record.index_keys() {
+                    txn.set(keyspace, &index_key.entry(&key), &[])?;
+                }
+            }
+        }
+
+        txn.commit()
     }
 
-    pub fn get_store() -> BackendResult<&'static DbState> {
-        KV.get().ok_or(BackendError::KvUninitialized)
+    /// Rewrites every record stored with an older schema version at the current
+    /// one in a single transaction. Records that fail to migrate are left untouched.
+    pub async fn migrate_records<T: Versioned>(
+        &self,
+        keyspace: Keyspace,
+    ) -> BackendResult<MigrationReport> {
+        let mut txn = self.begin().await?;
+
+        let outdated = txn
+            .entries(keyspace)?
+            .into_iter()
+            .filter(|(_, value)| stored_version(value) < T::SCHEMA_VERSION)
+            .collect::<Vec<(String, Vec<u8>)>>();
+
+        let mut report = MigrationReport::default();
+        for (key, value) in outdated {
+            match decode::<T>(&value) {
+                Ok(record) => {
+                    txn.set(keyspace, key.as_bytes(), &encode(&record)?)?;
+                    report.migrated += 1;
+                }
+                Err(_) => report.failed.push(key),
+            }
+        }
+        txn.commit()?;
+
+        Ok(report)
     }
 }
 
@@ -0,0 +1,135 @@
+use std::collections::BTreeMap;
+
+use trenchesfund_common::{Donation, Supporter, TokenTotal, TransferRequest};
+
+use crate::{decode, BackendResult, DbTransaction, IndexKey, Keyspace};
+
+/// Selects the donations of the ledger. The window runs from `from` included
+/// to `to` excluded, both in seconds since the UNIX epoch.
+#[derive(Debug, Default, Clone, PartialEq, Eq, FromForm)]
+pub struct DonationFilter {
+    pub publisher: Option<String>,
+    pub project: Option<String>,
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+}
+
+impl DonationFilter {
+    fn matches(&self, donation: &Donation) -> bool {
+        self.publisher
+            .as_ref()
+            .is_none_or(|publisher| &donation.publisher == publisher)
+            && self
+                .project
+                .as_ref()
+                .is_none_or(|project| donation.project.as_ref() == Some(project))
+            && self.from.is_none_or(|from| donation.donated_at >= from)
+            && self.to.is_none_or(|to| donation.donated_at < to)
+    }
+}
+
+/// Adds the donation paying a confirmed `transfer` to the ledger, once
+pub fn record_donation(txn: &mut DbTransaction, transfer: &TransferRequest) -> BackendResult<()> {
+    let Some(donation) = Donation::from_transfer(transfer) else {
+        return Ok(());
+    };
+
+    if txn
+        .get(Keyspace::Donations, donation.signature.as_bytes())?
+        .is_some()
+    {
+        return Ok(());
+    }
+
+    txn.create_indexed(Keyspace::Donations, &donation.signature, &donation)
+}
+
+/// The donations selected by `filter`, oldest first
+pub fn donations_in(
+    txn: &mut DbTransaction,
+    filter: &DonationFilter,
+) -> BackendResult<Vec<Donation>> {
+    let index_key = match (filter.project.as_ref(), filter.publisher.as_ref()) {
+        (Some(project), _) => Some(IndexKey::Project(project.clone())),
+        (None, Some(publisher)) => Some(IndexKey::Publisher(publisher.clone())),
+        (None, None) => None,
+    };
+
+    let records = match index_key {
+        Some(index_key) => txn
+            .index_lookup(Keyspace::Donations, &index_key)?
+            .iter()
+            .map(|key| txn.read(Keyspace::Donations, key))
+            .collect::<BackendResult<Vec<Vec<u8>>>>()?,
+        None => txn
+            .entries(Keyspace::Donations)?
+            .into_iter()
+            .map(|(_, bytes)| bytes)
+            .collect(),
+    };
+
+    let mut donations = records
+        .iter()
+        .map(|bytes| decode::<Donation>(bytes))
+        .collect::<BackendResult<Vec<Donation>>>()?;
+    donations.retain(|donation| filter.matches(donation));
+    donations.sort_by(|first, second| {
+        (first.donated_at, &first.signature).cmp(&(second.donated_at, &second.signature))
+    });
+
+    Ok(donations)
+}
+
+/// The sum of `donations` in each token, ordered by mint
+pub fn donation_totals(donations: &[Donation]) -> Vec<TokenTotal> {
+    let mut totals = BTreeMap::<&str, TokenTotal>::new();
+
+    for donation in donations {
+        let total = totals
+            .entry(donation.mint.as_str())
+            .or_insert_with(|| TokenTotal {
+                mint: donation.mint.clone(),
+                ..TokenTotal::default()
+            });
+        total.amount = total.amount.saturating_add(donation.amount);
+        total.donations += 1;
+    }
+
+    totals.into_values().collect()
+}
+
+/// The `limit` donors who gave the most in a token, largest first
+pub fn top_supporters(donations: &[Donation], limit: usize) -> Vec<Supporter> {
+    let mut supporters = BTreeMap::<(&str, &str), Supporter>::new();
+
+    for donation in donations {
+        let supporter = supporters
+            .entry((donation.donor.as_str(), donation.mint.as_str()))
+            .or_insert_with(|| Supporter {
+                donor: donation.donor.clone(),
+                mint: donation.mint.clone(),
+                ..Supporter::default()
+            });
+        supporter.amount = supporter.amount.saturating_add(donation.amount);
+        supporter.donations += 1;
+    }
+
+    let mut supporters = supporters.into_values().collect::<Vec<Supporter>>();
+    // Ties keep the order of the donor addresses so that the ranking is stable
+    supporters.sort_by_key(|supporter| core::cmp::Reverse(supporter.amount));
+    supporters.truncate(limit);
+
+    supporters
+}
+
+/// Moves the donations made to the publisher at `from` to its new address `to`
+pub fn relink_donations(txn: &mut DbTransaction, from: &str, to: &str) -> BackendResult<()> {
+    for key in txn.index_lookup(Keyspace::Donations, &IndexKey::Publisher(from.to_string()))? {
+        let mut donation = decode::<Donation>(&txn.read(Keyspace::Donations, &key)?)?;
+        donation.publisher = to.to_string();
+
+        txn.update_indexed(Keyspace::Donations, &key, &donation)?;
+    }
+
+    Ok(())
+}
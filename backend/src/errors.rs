@@ -1,37 +1,180 @@
-use serde::Deserialize;
+use rocket::{
+    http::Status,
+    request::Request,
+    response::{self, Responder},
+    serde::json::Json,
+};
+use trenchesfund_common::{ApiError, ErrorCode, FieldError, SignedRequestError};
 
 pub type BackendResult<T> = Result<T, BackendError>;
 
-#[derive(Debug, Deserialize)]
-#[serde(untagged)]
-pub enum JsonResponse<T, U> {
-    Success(T),
-    Failure(U),
-}
-
-#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+#[derive(Debug, thiserror::Error, PartialEq, Eq, Clone)]
 pub enum BackendError {
     #[error("KV: {0}")]
     Kv(String),
-    #[error("The KV is yet to be initialized")]
+    #[error("The KV is not managed by this Rocket instance")]
     KvUninitialized,
-    #[error("The KV is already initialized")]
-    KvAlreadyInitialized,
     #[error("They key already exists in the KV")]
     KvAlreadyExists,
     #[error("The key was not found in the KV")]
     KvKeyNotFound,
+    #[error("The records were changed by another request, try again")]
+    TransactionConflict,
+    #[error("Authentication is required for the resource requested")]
+    Unauthorized,
     #[error("Permission denied on the resource requested")]
     PermissionDenied,
+    #[error("The signature is invalid for the payload provided")]
+    InvalidSignature,
+    #[error("The signed request has expired")]
+    RequestExpired,
+    #[error("The nonce of the signed request has already been used")]
+    NonceReused,
+    #[error("The address is not a valid Ed25519 public key")]
+    InvalidPublicKey,
+    #[error("The session has expired, sign in again")]
+    SessionExpired,
+    #[error("Too many donations to this publisher are waiting for payment, try again later")]
+    TooManyPendingTransfers,
+    #[error("Bad request: {0}")]
+    BadRequest(String),
+    #[error("Invalid fields: {}", .0.iter().map(|field| field.to_string()).collect::<Vec<String>>().join(", "))]
+    InvalidFields(Vec<FieldError>),
     #[error("Unable to deserialize JSON")]
     JsonDeserialize,
     #[error("Unable to deserialize or deserialize bytes")]
     BincodeError,
+    #[error("The stored record has unsupported schema version `{0}`")]
+    UnsupportedSchemaVersion(u16),
+    #[error("Solana RPC: {0}")]
+    Rpc(String),
+    #[error("Invalid configuration: {0}")]
+    Config(String),
+    #[error("The database directory `{0}` is not writable: {1}")]
+    DatabaseDirUnwritable(String, String),
+}
+
+impl BackendError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::KvAlreadyExists => ErrorCode::AlreadyExists,
+            Self::KvKeyNotFound => ErrorCode::NotFound,
+            Self::TransactionConflict => ErrorCode::Conflict,
+            Self::TooManyPendingTransfers => ErrorCode::TooManyRequests,
+            Self::Unauthorized => ErrorCode::Unauthorized,
+            Self::PermissionDenied => ErrorCode::PermissionDenied,
+            Self::InvalidSignature => ErrorCode::InvalidSignature,
+            Self::RequestExpired => ErrorCode::RequestExpired,
+            Self::NonceReused => ErrorCode::NonceReused,
+            Self::InvalidPublicKey => ErrorCode::InvalidPublicKey,
+            Self::SessionExpired => ErrorCode::SessionExpired,
+            Self::BadRequest(_) | Self::InvalidFields(_) | Self::JsonDeserialize => {
+                ErrorCode::BadRequest
+            }
+            Self::Kv(_)
+            | Self::KvUninitialized
+            | Self::BincodeError
+            | Self::UnsupportedSchemaVersion(_)
+            | Self::Rpc(_)
+            | Self::Config(_)
+            | Self::DatabaseDirUnwritable(_, _) => ErrorCode::Internal,
+        }
+    }
+
+    pub fn status(&self) -> Status {
+        status_of(self.code())
+    }
+
+    /// Internal details are logged and never sent to the client
+    pub fn to_api_error(&self) -> ApiError {
+        if let Self::InvalidFields(fields) = self {
+            return ApiError::invalid_fields(fields.clone());
+        }
+
+        match self.code() {
+            ErrorCode::Internal => ApiError::new(ErrorCode::Internal, "Internal Server Error"),
+            code => ApiError::new(code, self),
+        }
+    }
+}
+
+pub fn status_of(code: ErrorCode) -> Status {
+    match code {
+        ErrorCode::BadRequest | ErrorCode::InvalidPublicKey => Status::BadRequest,
+        ErrorCode::Unauthorized
+        | ErrorCode::InvalidSignature
+        | ErrorCode::RequestExpired
+        | ErrorCode::NonceReused
+        | ErrorCode::SessionExpired => Status::Unauthorized,
+        ErrorCode::PermissionDenied => Status::Forbidden,
+        ErrorCode::NotFound => Status::NotFound,
+        ErrorCode::AlreadyExists | ErrorCode::Conflict => Status::Conflict,
+        ErrorCode::TooManyRequests => Status::TooManyRequests,
+        ErrorCode::Internal | ErrorCode::Network | ErrorCode::Wallet => Status::InternalServerError,
+    }
+}
+
+impl<'r> Responder<'r, 'static> for BackendError {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        if self.code() == ErrorCode::Internal {
+            rocket::error!("{} {}: {}", request.method(), request.uri(), self);
+        }
+
+        (self.status(), Json(self.to_api_error())).respond_to(request)
+    }
+}
+
+/// Set by request guards so that the catcher can report why the guard failed
+#[derive(Debug, Default)]
+pub struct GuardError(pub Option<BackendError>);
+
+impl GuardError {
+    pub fn record(request: &Request<'_>, error: &BackendError) {
+        request.local_cache(|| GuardError(Some(error.clone())));
+    }
+}
+
+/// Fails a request guard, recording the error for [default_catcher]
+pub fn fail<T>(
+    request: &Request<'_>,
+    error: BackendError,
+) -> rocket::request::Outcome<T, BackendError> {
+    GuardError::record(request, &error);
+
+    rocket::request::Outcome::Error((error.status(), error))
+}
+
+#[catch(default)]
+pub fn default_catcher(status: Status, request: &Request) -> (Status, Json<ApiError>) {
+    if let Some(error) = request.local_cache(GuardError::default).0.as_ref() {
+        return (status, Json(error.to_api_error()));
+    }
+
+    let code = match status.code {
+        401 => ErrorCode::Unauthorized,
+        403 => ErrorCode::PermissionDenied,
+        404 => ErrorCode::NotFound,
+        409 => ErrorCode::AlreadyExists,
+        429 => ErrorCode::TooManyRequests,
+        400..=499 => ErrorCode::BadRequest,
+        _ => ErrorCode::Internal,
+    };
+
+    (
+        status,
+        Json(ApiError::new(
+            code,
+            status.reason().unwrap_or("Unknown Error"),
+        )),
+    )
 }
 
 impl From<surrealkv::Error> for BackendError {
     fn from(value: surrealkv::Error) -> Self {
-        Self::Kv(value.to_string())
+        match value {
+            surrealkv::Error::TransactionWriteConflict => Self::TransactionConflict,
+            error => Self::Kv(error.to_string()),
+        }
     }
 }
 
@@ -41,8 +184,30 @@ impl From<serde_json::Error> for BackendError {
     }
 }
 
+impl From<Vec<FieldError>> for BackendError {
+    fn from(value: Vec<FieldError>) -> Self {
+        Self::InvalidFields(value)
+    }
+}
+
+impl From<reqwest::Error> for BackendError {
+    fn from(value: reqwest::Error) -> Self {
+        Self::Rpc(value.to_string())
+    }
+}
+
 impl From<bincode::Error> for BackendError {
     fn from(_: bincode::Error) -> Self {
         Self::BincodeError
     }
 }
+
+impl From<SignedRequestError> for BackendError {
+    fn from(value: SignedRequestError) -> Self {
+        match value {
+            SignedRequestError::Encoding => Self::BincodeError,
+            SignedRequestError::InvalidSignature => Self::InvalidSignature,
+            SignedRequestError::Expired => Self::RequestExpired,
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use ed25519_dalek::VerifyingKey;
+use trenchesfund_common::{Publisher, PublisherRevision};
+
+use crate::{decode, encode, BackendError, BackendResult, DbState, DbTransaction, Keyspace};
+
+const HISTORY_SEPARATOR: u8 = 0x00;
+/// Ends the key of the revision counter of a history, after every revision key
+const COUNTER_SEPARATOR: u8 = 0x01;
+
+/// The history of a publisher is kept under the address it was first recorded
+/// at so that rotating its key never rewrites it
+fn history_id(txn: &mut DbTransaction, address: &str) -> BackendResult<String> {
+    Ok(txn
+        .get(Keyspace::PublisherHistoryIds, address.as_bytes())?
+        .map(|id| String::from_utf8_lossy(&id).to_string())
+        .unwrap_or_else(|| address.to_string()))
+}
+
+/// Revisions of a publisher share this prefix, ordered by their big-endian number
+fn history_prefix(id: &str) -> Vec<u8> {
+    [id.as_bytes(), &[HISTORY_SEPARATOR]].concat()
+}
+
+fn counter_key(id: &str) -> Vec<u8> {
+    [id.as_bytes(), &[COUNTER_SEPARATOR]].concat()
+}
+
+/// The number of the next revision of the history `id`
+fn next_number(txn: &mut DbTransaction, id: &str) -> BackendResult<u64> {
+    Ok(txn
+        .get(Keyspace::PublisherHistory, &counter_key(id))?
+        .and_then(|bytes| bytes.try_into().ok())
+        .map_or(0, u64::from_be_bytes))
+}
+
+/// Appends `publisher` to its history as written by `signer` at `edited_at`
+pub fn record_revision(
+    txn: &mut DbTransaction,
+    publisher: &Publisher,
+    signer: &VerifyingKey,
+    edited_at: Option<u64>,
+) -> BackendResult<()> {
+    let id = history_id(txn, &publisher.address())?;
+    let number = next_number(txn, &id)?;
+    let key = [history_prefix(&id).as_slice(), &number.to_be_bytes()].concat();
+
+    if txn.get(Keyspace::PublisherHistory, &key)?.is_some() {
+        return Err(BackendError::KvAlreadyExists);
+    }
+
+    let revision = PublisherRevision {
+        publisher: publisher.clone(),
+        edited_at,
+        signer: *signer,
+    };
+
+    txn.set(Keyspace::PublisherHistory, &key, &encode(&revision)?)?;
+    // Concurrent edits both write the counter so all but one fail to commit
+    txn.set(
+        Keyspace::PublisherHistory,
+        &counter_key(&id),
+        &(number + 1).to_be_bytes(),
+    )
+}
+
+/// Whether any revision of the publisher at `address` was recorded
+pub fn has_history(txn: &mut DbTransaction, address: &str) -> BackendResult<bool> {
+    let id = history_id(txn, address)?;
+
+    Ok(next_number(txn, &id)? > 0)
+}
+
+/// Continues the history of the publisher at `from` once it moved to `to`,
+/// leaving every revision recorded so far where it is
+pub fn link_history(txn: &mut DbTransaction, from: &str, to: &str) -> BackendResult<()> {
+    let id = history_id(txn, from)?;
+
+    if id == to {
+        txn.delete(Keyspace::PublisherHistoryIds, to.as_bytes())
+    } else {
+        txn.set(Keyspace::PublisherHistoryIds, to.as_bytes(), id.as_bytes())
+    }
+}
+
+/// Every recorded version of the publisher at `address`, oldest first
+pub async fn publisher_history(
+    db: &DbState,
+    address: &str,
+) -> BackendResult<Vec<PublisherRevision>> {
+    let mut txn = db.begin_read().await?;
+    let id = history_id(&mut txn, address)?;
+
+    txn.scan_prefix(Keyspace::PublisherHistory, &history_prefix(&id))?
+        .into_iter()
+        .map(|(_, bytes)| decode::<PublisherRevision>(&bytes))
+        .collect()
+}
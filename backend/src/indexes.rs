@@ -0,0 +1,117 @@
+use trenchesfund_common::{Donation, Project, Publisher, TransferRequest};
+
+/// Keys of secondary index entries start with this byte. It never occurs in
+/// UTF-8 so index entries cannot collide with record keys and sort after them.
+pub const INDEX_PREFIX: u8 = 0xFF;
+
+const INDEX_SEPARATOR: u8 = 0x00;
+
+/// A secondary index term, stored in the same keyspace as the records it
+/// points to so that both are updated in one transaction
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum IndexKey {
+    Category(String),
+    Language(String),
+    Publisher(String),
+    /// [Project::alias], unique so that it can stand in for the project id
+    Alias(String),
+    /// Address of a key in [Publisher::signers]
+    Signer(String),
+    /// Base58 id of the project a [Donation] was made for
+    Project(String),
+}
+
+impl IndexKey {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Category(_) => "category",
+            Self::Language(_) => "language",
+            Self::Publisher(_) => "publisher",
+            Self::Alias(_) => "alias",
+            Self::Signer(_) => "signer",
+            Self::Project(_) => "project",
+        }
+    }
+
+    fn value(&self) -> &str {
+        match self {
+            Self::Category(value)
+            | Self::Language(value)
+            | Self::Publisher(value)
+            | Self::Alias(value)
+            | Self::Signer(value)
+            | Self::Project(value) => value,
+        }
+    }
+
+    /// All entries of this term share this prefix
+    pub fn prefix(&self) -> Vec<u8> {
+        let mut prefix = vec![INDEX_PREFIX];
+        prefix.extend_from_slice(self.name().as_bytes());
+        prefix.push(INDEX_SEPARATOR);
+        prefix.extend_from_slice(self.value().as_bytes());
+        prefix.push(INDEX_SEPARATOR);
+
+        prefix
+    }
+
+    /// The entry pointing to the record stored under `record_key`
+    pub fn entry(&self, record_key: &str) -> Vec<u8> {
+        let mut entry = self.prefix();
+        entry.extend_from_slice(record_key.as_bytes());
+
+        entry
+    }
+
+    /// Recovers the record key from an entry of this term
+    pub fn record_key(&self, entry: &[u8]) -> Option<String> {
+        entry
+            .strip_prefix(self.prefix().as_slice())
+            .map(|record_key| String::from_utf8_lossy(record_key).to_string())
+    }
+}
+
+/// Records whose secondary indexes are maintained by [crate::DbState]
+pub trait Indexed {
+    fn index_keys(&self) -> Vec<IndexKey>;
+}
+
+impl Indexed for Project {
+    fn index_keys(&self) -> Vec<IndexKey> {
+        vec![
+            IndexKey::Category(self.category.to_string()),
+            IndexKey::Language(self.language.to_string()),
+            IndexKey::Publisher(self.publisher_address().to_string()),
+            IndexKey::Alias(self.alias()),
+        ]
+    }
+}
+
+impl Indexed for Publisher {
+    fn index_keys(&self) -> Vec<IndexKey> {
+        self.signers
+            .iter()
+            .map(|signer| IndexKey::Signer(Publisher::public_key_to_base58(&signer.public_key)))
+            .collect()
+    }
+}
+
+impl Indexed for Donation {
+    fn index_keys(&self) -> Vec<IndexKey> {
+        let mut keys = vec![IndexKey::Publisher(self.publisher.clone())];
+        keys.extend(self.project.clone().map(IndexKey::Project));
+
+        keys
+    }
+}
+
+/// Only pending requests are indexed, so that those of a publisher can be counted
+impl Indexed for TransferRequest {
+    fn index_keys(&self) -> Vec<IndexKey> {
+        if self.is_confirmed() {
+            Vec::default()
+        } else {
+            vec![IndexKey::Publisher(self.recipient.clone())]
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use core::fmt;
+
+/// A dataset of the store. Every key of a keyspace starts with its
+/// [Keyspace::prefix] so that keyspaces never overlap.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Keyspace {
+    Publishers,
+    /// Every version of each publisher profile, never rewritten
+    PublisherHistory,
+    /// The history a publisher appends to, by each address it rotated to
+    PublisherHistoryIds,
+    /// The address a publisher moved to after rotating its key, by its old address
+    PublisherRedirects,
+    /// Approvals of a key rotation by the co-owners of a publisher, until it happens
+    RotationApprovals,
+    Projects,
+    /// Nonces of signed requests until they expire
+    Nonces,
+    /// Sign in challenges and sessions
+    Sessions,
+    /// The full-text search index
+    Search,
+    /// The ledger of confirmed donations by transaction signature
+    Donations,
+    /// Solana Pay transfer requests by their reference key, until they expire
+    /// unpaid or for as long again once confirmed
+    TransferRequests,
+    Reports,
+}
+
+impl Keyspace {
+    /// Ends the name of a keyspace in the keys of its entries. It never occurs
+    /// in a name so no keyspace is a prefix of another.
+    const SEPARATOR: u8 = 0x00;
+
+    pub const fn all() -> &'static [Self] {
+        &[
+            Self::Publishers,
+            Self::PublisherHistory,
+            Self::PublisherHistoryIds,
+            Self::PublisherRedirects,
+            Self::RotationApprovals,
+            Self::Projects,
+            Self::Nonces,
+            Self::Sessions,
+            Self::Search,
+            Self::Donations,
+            Self::TransferRequests,
+            Self::Reports,
+        ]
+    }
+
+    /// The name that starts the keys of its entries
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Publishers => "PUBLISHERS",
+            Self::PublisherHistory => "PUBLISHER_HISTORY",
+            Self::PublisherHistoryIds => "PUBLISHER_HISTORY_IDS",
+            Self::PublisherRedirects => "PUBLISHER_REDIRECTS",
+            Self::RotationApprovals => "ROTATION_APPROVALS",
+            Self::Projects => "PROJECTS",
+            Self::Nonces => "NONCES",
+            Self::Sessions => "SESSIONS",
+            Self::Search => "SEARCH",
+            Self::Donations => "DONATIONS",
+            Self::TransferRequests => "TRANSFER_REQUESTS",
+            Self::Reports => "REPORTS",
+        }
+    }
+
+    /// Every key of this keyspace starts with these bytes
+    pub fn prefix(&self) -> Vec<u8> {
+        [self.name().as_bytes(), &[Self::SEPARATOR]].concat()
+    }
+}
+
+impl fmt::Display for Keyspace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name().to_lowercase())
+    }
+}
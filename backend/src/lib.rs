@@ -0,0 +1,50 @@
+#[macro_use]
+extern crate rocket;
+
+mod auth;
+pub use auth::*;
+
+mod config;
+pub use config::*;
+
+mod db;
+pub use db::*;
+
+mod donations;
+pub use donations::*;
+
+mod errors;
+pub use errors::*;
+
+mod keyspace;
+pub use keyspace::*;
+
+mod history;
+pub use history::*;
+
+mod indexes;
+pub use indexes::*;
+
+mod publishers;
+pub use publishers::*;
+
+mod routes;
+pub use routes::*;
+
+mod schema;
+pub use schema::*;
+
+mod search;
+pub use search::*;
+
+mod session;
+pub use session::*;
+
+mod storage;
+pub use storage::*;
+
+mod transaction;
+pub use transaction::*;
+
+mod transfers;
+pub use transfers::*;
@@ -1,97 +1,77 @@
-use async_lock::OnceCell;
-use rocket::{fs::FileServer, serde::json::Json};
-use trenchesfund_common::{Outcome, Project, Publisher};
+use rocket::fs::FileServer;
+use trenchesfund_backend::*;
+use trenchesfund_common::{Donation, Project, Publisher, TransferRequest};
 
-mod db;
-pub(crate) use db::*;
+const PURGE_INTERVAL_SECS: u64 = 60;
 
-mod errors;
-pub(crate) use errors::*;
+#[rocket::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let rocket = rocket::build();
+    let config = DatabaseConfig::from_figment(rocket.figment())
+        .unwrap_or_else(|error| panic!("Unable to read the database configuration: {error}"));
 
-pub(crate) static KV: OnceCell<DbState> = OnceCell::new();
+    let solana = SolanaConfig::from_figment(rocket.figment())
+        .unwrap_or_else(|error| panic!("Unable to read the Solana configuration: {error}"));
 
-pub(crate) const PUBLISHERS_DB: &str = "PUBLISHERS";
-pub(crate) const PROJECTS_DB: &str = "PROJECTS";
+    let db = DbState::open(&config)
+        .await
+        .unwrap_or_else(|error| panic!("Unable to open the database: {error}"));
+    let assets_path = concat!(env!("CARGO_WORKSPACE_DIR"), "public");
 
-#[macro_use]
-extern crate rocket;
+    for (keyspace, report) in migrate_all(&db).await? {
+        if report.migrated > 0 {
+            println!("Migrated {} {keyspace} records", report.migrated);
+        }
+        if !report.failed.is_empty() {
+            eprintln!("Unable to migrate {keyspace} records: {:?}", report.failed);
+        }
+    }
 
-#[post("/projects")]
-async fn projects() -> Json<Outcome<Vec<Project>>> {
-    if let Ok(projects_raw) = DbState::values(PROJECTS_DB).await {
-        let mut projects = Vec::<Project>::default();
+    db.rebuild_indexes::<Project>(Keyspace::Projects).await?;
+    db.rebuild_indexes::<Publisher>(Keyspace::Publishers)
+        .await?;
+    db.rebuild_indexes::<Donation>(Keyspace::Donations).await?;
+    db.rebuild_indexes::<TransferRequest>(Keyspace::TransferRequests)
+        .await?;
+    rebuild_search_index(&db).await?;
 
-        if projects_raw
-            .into_iter()
-            .try_for_each(|project_bytes| {
-                projects.push(bincode::deserialize::<Project>(&project_bytes)?);
+    let purge_db = db.clone();
+    tokio::spawn(async move {
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(PURGE_INTERVAL_SECS));
 
-                Ok::<(), BackendError>(())
-            })
-            .is_ok()
-        {
-            Json(Outcome::Success(projects))
-        } else {
-            Json(Outcome::Failure("Internal Server Error".to_string()))
+        loop {
+            interval.tick().await;
+            let _ = purge_expired_nonces(&purge_db).await;
+            let _ = purge_expired_sessions(&purge_db).await;
         }
-    } else {
-        Json(Outcome::Failure("Internal Server Error".to_string()))
-    }
-}
+    });
 
-#[post("/project-info/<id>")]
-async fn projects_info(id: String) -> Json<Outcome<Option<Project>>> {
-    match DbState::read(PROJECTS_DB, &id).await {
-        Ok(project_exists) => {
-            if let Ok(value) = bincode::deserialize::<Project>(&project_exists) {
-                Json(Outcome::Success(Some(value)))
-            } else {
-                Json(Outcome::Failure("Internal Error".to_string()))
-            }
-        }
-        Err(error) => {
-            if error == BackendError::KvKeyNotFound {
-                Json(Outcome::Success(Option::None))
-            } else {
-                Json(Outcome::Failure("Internal Server Error".to_string()))
-            }
-        }
-    }
-}
+    let watch_db = db.clone();
+    tokio::spawn(async move {
+        let rpc = SolanaRpc::new(solana.rpc_url);
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(solana.poll_interval_secs));
 
-#[post("/publisher-info/<id>")]
-async fn publisher_info(id: String) -> Json<Outcome<Option<Publisher>>> {
-    match DbState::read(PUBLISHERS_DB, &id).await {
-        Ok(project_exists) => {
-            if let Ok(value) = bincode::deserialize::<Publisher>(&project_exists) {
-                Json(Outcome::Success(Some(value)))
-            } else {
-                Json(Outcome::Failure("Internal Error".to_string()))
+        loop {
+            interval.tick().await;
+            if let Err(error) = confirm_transfers(
+                &watch_db,
+                &rpc,
+                unix_timestamp(),
+                solana.request_validity_secs,
+            )
+            .await
+            {
+                eprintln!("Unable to check the pending transfer requests: {error}");
             }
         }
-        Err(error) => {
-            if error == BackendError::KvKeyNotFound {
-                Json(Outcome::Success(Option::None))
-            } else {
-                Json(Outcome::Failure("Internal Server Error".to_string()))
-            }
-        }
-    }
-}
+    });
 
-#[rocket::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    if DbState::init().await.is_err() {
-        panic!("KV store already initialized!");
-    } else {
-        let assets_path = concat!(env!("CARGO_WORKSPACE_DIR"), "public");
+    let _ = mount_api(rocket, db)
+        .mount("/", FileServer::from(assets_path))
+        .launch()
+        .await?;
 
-        let _ = rocket::build()
-            .mount("/", FileServer::from(assets_path))
-            .mount("/", routes![projects, projects_info, publisher_info])
-            .launch()
-            .await?;
-
-        Ok(())
-    }
+    Ok(())
 }
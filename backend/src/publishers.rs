@@ -0,0 +1,190 @@
+use ed25519_dalek::VerifyingKey;
+use trenchesfund_common::{KeyRotation, Project, Publisher, SignerRole};
+
+use crate::{
+    authorize_publisher, decode, has_history, index_project, index_publisher, link_history,
+    record_revision, relink_donations, unindex, BackendError, BackendResult, DbTransaction,
+    IndexKey, Keyspace, SearchDocument,
+};
+
+/// Rewrites the copy of `publisher` held by the projects listed under `address`
+pub fn relink_projects(
+    txn: &mut DbTransaction,
+    address: &str,
+    publisher: &Publisher,
+) -> BackendResult<()> {
+    for key in txn.index_lookup(
+        Keyspace::Projects,
+        &IndexKey::Publisher(address.to_string()),
+    )? {
+        let mut project = decode::<Project>(&txn.read(Keyspace::Projects, &key)?)?;
+        project.publisher = (
+            publisher.short_address(),
+            publisher.address(),
+            publisher.name.clone(),
+        );
+
+        txn.update_indexed(Keyspace::Projects, &key, &project)?;
+        index_project(txn, &key, &project)?;
+    }
+
+    Ok(())
+}
+
+/// The address the publisher at `address` moved to, if it rotated its key
+pub fn publisher_redirect(txn: &mut DbTransaction, address: &str) -> BackendResult<Option<String>> {
+    Ok(txn
+        .get(Keyspace::PublisherRedirects, address.as_bytes())?
+        .map(|bytes| String::from_utf8_lossy(&bytes).to_string()))
+}
+
+/// Points `from`, and every address that already redirects to it, at `to`
+fn redirect_publisher(txn: &mut DbTransaction, from: &str, to: &str) -> BackendResult<()> {
+    for (key, target) in txn.entries(Keyspace::PublisherRedirects)? {
+        if target == from.as_bytes() {
+            txn.set(Keyspace::PublisherRedirects, key.as_bytes(), to.as_bytes())?;
+        }
+    }
+
+    // A key rotated back to no longer redirects
+    txn.delete(Keyspace::PublisherRedirects, to.as_bytes())?;
+
+    txn.set(Keyspace::PublisherRedirects, from.as_bytes(), to.as_bytes())
+}
+
+/// Moves the `publisher` stored at `from` to its address along with its
+/// history, search entry, projects and donations, leaving a redirect at `from`
+pub fn move_publisher(
+    txn: &mut DbTransaction,
+    from: &str,
+    publisher: &Publisher,
+) -> BackendResult<String> {
+    let address = publisher.address();
+    txn.remove_indexed::<Publisher>(Keyspace::Publishers, from)?;
+    txn.create_indexed(Keyspace::Publishers, &address, publisher)?;
+
+    link_history(txn, from, &address)?;
+    redirect_publisher(txn, from, &address)?;
+
+    unindex(txn, &SearchDocument::Publisher(from.to_string()))?;
+    index_publisher(txn, publisher)?;
+    relink_projects(txn, from, publisher)?;
+    relink_donations(txn, from, &address)?;
+
+    Ok(address)
+}
+
+/// How long the approval of a rotation by a co-owner counts towards it
+const ROTATION_APPROVAL_VALIDITY_SECS: u64 = 60 * 60 * 24 * 7;
+
+/// Approvals of moving the publisher at `address` to `new_key` share this prefix
+fn approval_prefix(address: &str, new_key: &VerifyingKey) -> String {
+    String::new() + address + ":" + &Publisher::public_key_to_base58(new_key) + ":"
+}
+
+/// The co-owners that must approve a rotation the current key did not sign,
+/// more than half of all owners
+fn rotation_quorum(publisher: &Publisher) -> usize {
+    let owners = 1 + publisher
+        .signers
+        .iter()
+        .filter(|signer| signer.role == SignerRole::Owner)
+        .count();
+
+    owners / 2 + 1
+}
+
+/// Records the approval of `rotation` by the co-owner `signer`, returning
+/// whether enough owners approved it within [ROTATION_APPROVAL_VALIDITY_SECS]
+fn approve_rotation(
+    txn: &mut DbTransaction,
+    current: &Publisher,
+    rotation: &KeyRotation,
+    signer: &VerifyingKey,
+    rotated_at: u64,
+) -> BackendResult<bool> {
+    let prefix = approval_prefix(&rotation.address, &rotation.new_key);
+    txn.set(
+        Keyspace::RotationApprovals,
+        (prefix.clone() + &Publisher::public_key_to_base58(signer)).as_bytes(),
+        &rotated_at.to_le_bytes(),
+    )?;
+
+    // Approvals of keys that are no longer owners or that expired do not count
+    let approvals = txn
+        .scan_prefix(Keyspace::RotationApprovals, prefix.as_bytes())?
+        .into_iter()
+        .filter(|(_, approved_at)| {
+            approved_at
+                .as_slice()
+                .try_into()
+                .map(u64::from_le_bytes)
+                .is_ok_and(|approved_at| {
+                    approved_at.saturating_add(ROTATION_APPROVAL_VALIDITY_SECS) >= rotated_at
+                })
+        })
+        .filter_map(|(key, _)| {
+            Publisher::base58_to_public_key(&String::from_utf8_lossy(&key[prefix.len()..]))
+        })
+        .filter(|approver| current.role_of(approver) == Some(SignerRole::Owner))
+        .count();
+
+    Ok(approvals >= rotation_quorum(current))
+}
+
+/// Moves a publisher to the key of one of its owners, see [move_publisher],
+/// returning the address it is found at afterwards. The new key must already
+/// be an owner so that a publisher is only moved to a key it vouched for, and
+/// the old key is dropped from the signers.
+///
+/// The current key rotates the publisher at once. So that a lost or
+/// compromised key can still be replaced without letting one co-owner evict
+/// the others, a rotation signed by a co-owner is only recorded as its
+/// approval and happens once more than half of all owners approved it. A
+/// publisher with a single co-owner can therefore only be rotated by its
+/// current key.
+pub fn rotate_publisher(
+    txn: &mut DbTransaction,
+    rotation: &KeyRotation,
+    signer: &VerifyingKey,
+    rotated_at: u64,
+) -> BackendResult<String> {
+    let current = authorize_publisher(txn, &rotation.address, signer, SignerRole::Owner)?;
+    if current.public_key == rotation.new_key {
+        return Err(BackendError::BadRequest(
+            "The publisher already uses this key".to_string(),
+        ));
+    }
+    if current.role_of(&rotation.new_key) != Some(SignerRole::Owner) {
+        return Err(BackendError::BadRequest(
+            "A publisher can only be rotated to the key of one of its owners".to_string(),
+        ));
+    }
+
+    if &current.public_key != signer
+        && !approve_rotation(txn, &current, rotation, signer, rotated_at)?
+    {
+        return Ok(rotation.address.clone());
+    }
+
+    let mut rotated = current.clone();
+    rotated.public_key = rotation.new_key;
+    rotated
+        .signers
+        .retain(|signer| signer.public_key != rotation.new_key);
+
+    if !has_history(txn, &rotation.address)? {
+        record_revision(txn, &current, &current.public_key, None)?;
+    }
+    let address = move_publisher(txn, &rotation.address, &rotated)?;
+    record_revision(txn, &rotated, signer, Some(rotated_at))?;
+
+    for (key, _) in txn.scan_prefix(
+        Keyspace::RotationApprovals,
+        (rotation.address.clone() + ":").as_bytes(),
+    )? {
+        txn.delete(Keyspace::RotationApprovals, &key)?;
+    }
+
+    Ok(address)
+}
@@ -0,0 +1,669 @@
+use std::{ops::Bound, str::FromStr};
+
+use ed25519_dalek::VerifyingKey;
+use rocket::{
+    http::{Cookie, CookieJar, SameSite},
+    response::Redirect,
+    serde::json::Json,
+    Build, Either, Rocket, State,
+};
+use trenchesfund_common::{
+    CodeLanguage, DonationRequest, KeyRotation, Onboarding, Project, ProjectCategory, ProjectPage,
+    Publisher, PublisherRevision, SearchResults, Session, SignIn, SignInChallenge, SignedRequest,
+    SignerRole, Supporter, TokenTotal, TransferRequest, Validate,
+};
+
+use crate::*;
+
+const DEFAULT_PAGE_LIMIT: usize = 24;
+const MAX_PAGE_LIMIT: usize = 100;
+const SCAN_BATCH_SIZE: usize = 256;
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+const DEFAULT_SUPPORTERS_LIMIT: usize = 10;
+const MAX_SUPPORTERS_LIMIT: usize = 100;
+
+/// Mounts the API routes and catchers on `rocket`, managing `db` as their storage
+pub fn mount_api(rocket: Rocket<Build>, db: DbState) -> Rocket<Build> {
+    rocket
+        .manage(db)
+        .mount(
+            "/",
+            routes![
+                projects,
+                full_text_search,
+                projects_info,
+                project_alias_info,
+                publisher_info,
+                publisher_info_history,
+                register_publisher,
+                update_publisher,
+                rotate_publisher_key,
+                onboard_publisher,
+                create_project,
+                update_project,
+                remove_project,
+                auth_challenge,
+                auth_login,
+                auth_session,
+                auth_logout,
+                publisher_me,
+                create_transfer_request,
+                transfer_request_status,
+                funding_totals,
+                funding_supporters
+            ],
+        )
+        .register("/", catchers![default_catcher])
+}
+
+#[derive(Debug, FromForm)]
+struct ProjectQuery {
+    cursor: Option<String>,
+    limit: Option<usize>,
+    category: Option<String>,
+    language: Option<String>,
+    publisher: Option<String>,
+}
+
+#[get("/projects?<query..>")]
+async fn projects(db: &State<DbState>, query: ProjectQuery) -> BackendResult<Json<ProjectPage>> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_PAGE_LIMIT)
+        .clamp(1, MAX_PAGE_LIMIT);
+    let category = query
+        .category
+        .as_deref()
+        .map(ProjectCategory::from_str)
+        .transpose()
+        .map_err(BackendError::BadRequest)?;
+    let language = query
+        .language
+        .as_deref()
+        .map(CodeLanguage::from_str)
+        .transpose()
+        .map_err(BackendError::BadRequest)?;
+
+    let index_keys = [
+        category.map(|value| IndexKey::Category(value.to_string())),
+        language.map(|value| IndexKey::Language(value.to_string())),
+        query.publisher.clone().map(IndexKey::Publisher),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<IndexKey>>();
+
+    let mut page = ProjectPage::default();
+    let mut cursor = query.cursor.clone();
+    // One snapshot for the whole page, so concurrent writes cannot tear it
+    let mut txn = db.begin_read().await?;
+
+    if index_keys.is_empty() {
+        'scan: loop {
+            let start = match cursor.as_deref() {
+                Some(key) => Bound::Excluded(key.as_bytes()),
+                None => Bound::Unbounded,
+            };
+            let batch = txn.scan(
+                Keyspace::Projects,
+                (start, RECORDS_END),
+                Some(SCAN_BATCH_SIZE),
+            )?;
+            let exhausted = batch.len() < SCAN_BATCH_SIZE;
+
+            for (key, project_bytes) in batch {
+                // Only a full page followed by another record has a next page
+                if page.projects.len() == limit {
+                    page.next_cursor = cursor;
+                    break 'scan;
+                }
+
+                let key = String::from_utf8_lossy(&key).to_string();
+                match decode::<Project>(&project_bytes) {
+                    Ok(project) => page.projects.push(project),
+                    Err(_) => page.skipped.push(key.clone()),
+                }
+
+                cursor.replace(key);
+            }
+
+            if exhausted {
+                break;
+            }
+        }
+    } else {
+        let mut candidates = Option::<Vec<String>>::None;
+
+        for index_key in &index_keys {
+            let keys = txn.index_lookup(Keyspace::Projects, index_key)?;
+
+            candidates = Some(match candidates {
+                None => keys,
+                Some(current) => current
+                    .into_iter()
+                    .filter(|key| keys.binary_search(key).is_ok())
+                    .collect(),
+            });
+        }
+
+        let candidates = candidates
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|key| cursor.as_ref().is_none_or(|cursor| key > cursor))
+            .collect::<Vec<String>>();
+
+        for key in candidates {
+            let Some(project_bytes) = txn.get(Keyspace::Projects, key.as_bytes())? else {
+                continue;
+            };
+
+            if page.projects.len() == limit {
+                page.next_cursor = cursor;
+                break;
+            }
+
+            match decode::<Project>(&project_bytes) {
+                Ok(project) => page.projects.push(project),
+                Err(_) => page.skipped.push(key.clone()),
+            }
+
+            cursor.replace(key);
+        }
+    }
+
+    Ok(Json(page))
+}
+
+#[get("/search?<q>&<limit>")]
+async fn full_text_search(
+    db: &State<DbState>,
+    q: &str,
+    limit: Option<usize>,
+) -> BackendResult<Json<SearchResults>> {
+    let limit = limit
+        .unwrap_or(DEFAULT_SEARCH_LIMIT)
+        .clamp(1, MAX_PAGE_LIMIT);
+
+    Ok(Json(search(db, q, limit).await?))
+}
+
+/// The key of the project with `id` encoded as hex or base58
+fn project_key(id: &str) -> BackendResult<String> {
+    Project::parse_id(id)
+        .map(|id| Project::id_to_base58(&id))
+        .ok_or(BackendError::KvKeyNotFound)
+}
+
+#[post("/project-info/<id>")]
+async fn projects_info(db: &State<DbState>, id: &str) -> BackendResult<Json<Project>> {
+    let project = decode::<Project>(&db.read(Keyspace::Projects, &project_key(id)?).await?)?;
+
+    Ok(Json(project))
+}
+
+/// A record, or a permanent redirect to where it moved
+type Moved<T> = Either<Json<T>, Redirect>;
+
+/// Redirects to `uri` of the address the publisher at `address` rotated to,
+/// [BackendError::KvKeyNotFound] if it never did
+fn publisher_moved(
+    txn: &mut DbTransaction,
+    address: &str,
+    uri: impl FnOnce(&str) -> String,
+) -> BackendResult<Redirect> {
+    publisher_redirect(txn, address)?
+        .map(|moved_to| Redirect::permanent(uri(&moved_to)))
+        .ok_or(BackendError::KvKeyNotFound)
+}
+
+#[post("/project-info/<publisher>/<slug>")]
+async fn project_alias_info(
+    db: &State<DbState>,
+    publisher: &str,
+    slug: &str,
+) -> BackendResult<Moved<Project>> {
+    let slug = Project::slugify(slug);
+    let alias = String::new() + publisher + "/" + slug.as_str();
+    let mut txn = db.begin_read().await?;
+
+    let Some(key) = txn
+        .index_lookup(Keyspace::Projects, &IndexKey::Alias(alias))?
+        .into_iter()
+        .next()
+    else {
+        let redirect = publisher_moved(&mut txn, publisher, |moved_to| {
+            String::from("/project-info/") + moved_to + "/" + slug.as_str()
+        })?;
+
+        return Ok(Either::Right(redirect));
+    };
+
+    Ok(Either::Left(Json(decode::<Project>(
+        &txn.read(Keyspace::Projects, &key)?,
+    )?)))
+}
+
+#[post("/publisher-info/<id>")]
+async fn publisher_info(db: &State<DbState>, id: String) -> BackendResult<Moved<Publisher>> {
+    let mut txn = db.begin_read().await?;
+
+    match txn.read(Keyspace::Publishers, &id) {
+        Ok(bytes) => Ok(Either::Left(Json(decode::<Publisher>(&bytes)?))),
+        Err(BackendError::KvKeyNotFound) => {
+            Ok(Either::Right(publisher_moved(&mut txn, &id, |moved_to| {
+                String::from("/publisher-info/") + moved_to
+            })?))
+        }
+        Err(error) => Err(error),
+    }
+}
+
+#[get("/publisher-info/<id>/history")]
+async fn publisher_info_history(
+    db: &State<DbState>,
+    id: String,
+) -> BackendResult<Moved<Vec<PublisherRevision>>> {
+    let mut txn = db.begin_read().await?;
+
+    match txn.read(Keyspace::Publishers, &id) {
+        Ok(_) => Ok(Either::Left(Json(publisher_history(db, &id).await?))),
+        Err(BackendError::KvKeyNotFound) => {
+            Ok(Either::Right(publisher_moved(&mut txn, &id, |moved_to| {
+                String::from("/publisher-info/") + moved_to + "/history"
+            })?))
+        }
+        Err(error) => Err(error),
+    }
+}
+
+#[post("/publishers", data = "<request>")]
+async fn register_publisher(
+    db: &State<DbState>,
+    request: Json<SignedRequest<Publisher>>,
+) -> BackendResult<Json<String>> {
+    let request = request.into_inner();
+    let mut txn = db.begin().await?;
+    authenticate(&mut txn, &request)?;
+
+    let address = store_publisher(
+        &mut txn,
+        &request.payload,
+        &request.signer,
+        request.timestamp,
+    )?;
+    txn.commit()?;
+
+    Ok(Json(address))
+}
+
+/// Replaces the profile of a publisher, recording the new version in its history
+#[post("/publisher-update", data = "<request>")]
+async fn update_publisher(
+    db: &State<DbState>,
+    request: Json<SignedRequest<Publisher>>,
+) -> BackendResult<Json<String>> {
+    let request = request.into_inner();
+    let mut txn = db.begin().await?;
+    authenticate(&mut txn, &request)?;
+
+    let publisher = request.payload;
+    let address = publisher.address();
+    let current = authorize_publisher(&mut txn, &address, &request.signer, SignerRole::Editor)?;
+
+    // Editors maintain the profile, only owners decide who may sign and where payouts go
+    if (publisher.signers != current.signers || publisher.mint != current.mint)
+        && current.role_of(&request.signer) != Some(SignerRole::Owner)
+    {
+        return Err(BackendError::PermissionDenied);
+    }
+    publisher.validate()?;
+    check_signers(&publisher)?;
+
+    // Profiles registered before history was kept start it with their first version
+    if !has_history(&mut txn, &address)? {
+        record_revision(&mut txn, &current, &current.public_key, None)?;
+    }
+
+    txn.update_indexed(Keyspace::Publishers, &address, &publisher)?;
+    record_revision(
+        &mut txn,
+        &publisher,
+        &request.signer,
+        Some(request.timestamp),
+    )?;
+    index_publisher(&mut txn, &publisher)?;
+
+    // Projects keep a copy of the publisher name
+    if current.name != publisher.name {
+        relink_projects(&mut txn, &address, &publisher)?;
+    }
+
+    txn.commit()?;
+
+    Ok(Json(address))
+}
+
+/// Moves a publisher to a new key or approves moving it, see [rotate_publisher].
+/// Responds with the address of the publisher, unchanged until the move happens.
+#[post("/publisher-rotate", data = "<request>")]
+async fn rotate_publisher_key(
+    db: &State<DbState>,
+    request: Json<SignedRequest<KeyRotation>>,
+) -> BackendResult<Json<String>> {
+    let request = request.into_inner();
+    let mut txn = db.begin().await?;
+    authenticate(&mut txn, &request)?;
+    request.payload.validate()?;
+
+    let address = rotate_publisher(
+        &mut txn,
+        &request.payload,
+        &request.signer,
+        request.timestamp,
+    )?;
+    txn.commit()?;
+
+    Ok(Json(address))
+}
+
+#[post("/publisher-onboard", data = "<request>")]
+async fn onboard_publisher(
+    db: &State<DbState>,
+    request: Json<SignedRequest<Onboarding>>,
+) -> BackendResult<Json<String>> {
+    let request = request.into_inner();
+    let mut txn = db.begin().await?;
+    authenticate(&mut txn, &request)?;
+
+    let Onboarding { publisher, project } = request.payload;
+    store_publisher(&mut txn, &publisher, &request.signer, request.timestamp)?;
+    let name = store_project(&mut txn, project, &request.signer)?;
+    txn.commit()?;
+
+    Ok(Json(name))
+}
+
+/// Stores a new publisher registered by `signer` at `registered_at`, returning its address
+fn store_publisher(
+    txn: &mut DbTransaction,
+    publisher: &Publisher,
+    signer: &VerifyingKey,
+    registered_at: u64,
+) -> BackendResult<String> {
+    if &publisher.public_key != signer {
+        return Err(BackendError::PermissionDenied);
+    }
+
+    insert_publisher(txn, publisher, registered_at)
+}
+
+/// Validates and stores a new publisher with its first revision and search
+/// entry, returning its address. Callers check who may register it.
+pub fn insert_publisher(
+    txn: &mut DbTransaction,
+    publisher: &Publisher,
+    registered_at: u64,
+) -> BackendResult<String> {
+    publisher.validate()?;
+    check_signers(publisher)?;
+
+    // The address of a rotated key keeps pointing to where its publisher moved
+    let address = publisher.address();
+    if publisher_redirect(txn, &address)?.is_some() {
+        return Err(BackendError::KvAlreadyExists);
+    }
+
+    txn.create_indexed(Keyspace::Publishers, &address, publisher)?;
+    record_revision(txn, publisher, &publisher.public_key, Some(registered_at))?;
+    index_publisher(txn, publisher)?;
+
+    Ok(address)
+}
+
+/// Fails unless every signer of `publisher` is listed once and apart from its own key
+fn check_signers(publisher: &Publisher) -> BackendResult<()> {
+    for (index, signer) in publisher.signers.iter().enumerate() {
+        if signer.public_key == publisher.public_key
+            || publisher.signers[..index]
+                .iter()
+                .any(|listed| listed.public_key == signer.public_key)
+        {
+            return Err(BackendError::BadRequest(
+                "Each signer must be listed once and differ from the publisher key".to_string(),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Stores a new project of a publisher signed for by `signer`, see [insert_project]
+fn store_project(
+    txn: &mut DbTransaction,
+    project: Project,
+    signer: &VerifyingKey,
+) -> BackendResult<String> {
+    let publisher =
+        authorize_publisher(txn, project.publisher_address(), signer, SignerRole::Editor)?;
+
+    insert_project(txn, project, publisher)
+}
+
+/// Validates and stores a new project of `publisher` under the id derived
+/// from its publisher and name, returning the id. Callers check who may list it.
+pub fn insert_project(
+    txn: &mut DbTransaction,
+    mut project: Project,
+    publisher: Publisher,
+) -> BackendResult<String> {
+    project.publisher = (
+        publisher.short_address(),
+        publisher.address(),
+        publisher.name,
+    );
+    project.validate()?;
+    project.id = Project::derive_id(project.publisher_address(), &project.slug());
+
+    let key = project.id_base58();
+    check_alias(txn, &project, &key)?;
+
+    txn.create_indexed(Keyspace::Projects, &key, &project)?;
+    index_project(txn, &key, &project)?;
+
+    Ok(key)
+}
+
+/// Fails unless the alias of `project` is free or already points to `key`
+fn check_alias(txn: &mut DbTransaction, project: &Project, key: &str) -> BackendResult<()> {
+    let owners = txn.index_lookup(Keyspace::Projects, &IndexKey::Alias(project.alias()))?;
+
+    if owners.iter().any(|owner| owner != key) {
+        return Err(BackendError::KvAlreadyExists);
+    }
+
+    Ok(())
+}
+
+#[post("/project-create", data = "<request>")]
+async fn create_project(
+    db: &State<DbState>,
+    request: Json<SignedRequest<Project>>,
+) -> BackendResult<Json<String>> {
+    let request = request.into_inner();
+    let mut txn = db.begin().await?;
+    authenticate(&mut txn, &request)?;
+
+    let name = store_project(&mut txn, request.payload, &request.signer)?;
+    txn.commit()?;
+
+    Ok(Json(name))
+}
+
+#[post("/project-update", data = "<request>")]
+async fn update_project(
+    db: &State<DbState>,
+    request: Json<SignedRequest<Project>>,
+) -> BackendResult<Json<String>> {
+    let request = request.into_inner();
+    let mut txn = db.begin().await?;
+    authenticate(&mut txn, &request)?;
+
+    let mut project = request.payload;
+    let key = project.id_base58();
+    let stored = decode::<Project>(&txn.read(Keyspace::Projects, &key)?)?;
+
+    if stored.publisher_address() != project.publisher_address() {
+        return Err(BackendError::PermissionDenied);
+    }
+
+    let publisher = authorize_publisher(
+        &mut txn,
+        project.publisher_address(),
+        &request.signer,
+        SignerRole::Editor,
+    )?;
+
+    project.publisher = (
+        publisher.short_address(),
+        publisher.address(),
+        publisher.name,
+    );
+    project.validate()?;
+
+    check_alias(&mut txn, &project, &key)?;
+
+    txn.update_indexed(Keyspace::Projects, &key, &project)?;
+    index_project(&mut txn, &key, &project)?;
+    txn.commit()?;
+
+    Ok(Json(key))
+}
+
+#[post("/project-remove", data = "<request>")]
+async fn remove_project(
+    db: &State<DbState>,
+    request: Json<SignedRequest<String>>,
+) -> BackendResult<Json<String>> {
+    let request = request.into_inner();
+    let mut txn = db.begin().await?;
+    authenticate(&mut txn, &request)?;
+
+    let key = project_key(&request.payload)?;
+    let stored = decode::<Project>(&txn.read(Keyspace::Projects, &key)?)?;
+
+    authorize_publisher(
+        &mut txn,
+        stored.publisher_address(),
+        &request.signer,
+        SignerRole::Editor,
+    )?;
+
+    txn.remove_indexed::<Project>(Keyspace::Projects, &key)?;
+    unindex(&mut txn, &SearchDocument::Project(key.clone()))?;
+    txn.commit()?;
+
+    Ok(Json(key))
+}
+
+#[post("/auth/challenge/<address>")]
+async fn auth_challenge(
+    db: &State<DbState>,
+    address: String,
+) -> BackendResult<Json<SignInChallenge>> {
+    Ok(Json(issue_challenge(db, &address).await?))
+}
+
+#[post("/auth/login", data = "<request>")]
+async fn auth_login(
+    db: &State<DbState>,
+    request: Json<SignIn>,
+    cookies: &CookieJar<'_>,
+) -> BackendResult<Json<Session>> {
+    let session = sign_in(db, &request).await?;
+
+    cookies.add(
+        Cookie::build((SESSION_COOKIE, session.token.clone()))
+            .http_only(true)
+            .same_site(SameSite::Strict)
+            .max_age(rocket::time::Duration::seconds(
+                SESSION_VALIDITY_SECS as i64,
+            )),
+    );
+
+    Ok(Json(session))
+}
+
+#[post("/auth/session")]
+async fn auth_session(session: AuthenticatedSession) -> Json<Session> {
+    Json(session.0)
+}
+
+#[post("/auth/logout")]
+async fn auth_logout(
+    db: &State<DbState>,
+    session: AuthenticatedSession,
+    cookies: &CookieJar<'_>,
+) -> BackendResult<Json<()>> {
+    cookies.remove(Cookie::from(SESSION_COOKIE));
+    sign_out(db, &session.0.token).await?;
+
+    Ok(Json(()))
+}
+
+#[post("/publisher/me")]
+fn publisher_me(caller: AuthenticatedPublisher) -> Json<Publisher> {
+    Json(caller.publisher)
+}
+
+/// Starts a Solana Pay donation to a publisher, see [request_transfer]
+#[post("/transfer-request", data = "<request>")]
+async fn create_transfer_request(
+    db: &State<DbState>,
+    request: Json<DonationRequest>,
+) -> BackendResult<Json<TransferRequest>> {
+    let mut txn = db.begin().await?;
+    let transfer = request_transfer(&mut txn, &request, unix_timestamp())?;
+    txn.commit()?;
+
+    Ok(Json(transfer))
+}
+
+/// The transfer request with `reference`, polled by donors until it is confirmed
+#[get("/transfer-request/<reference>")]
+async fn transfer_request_status(
+    db: &State<DbState>,
+    reference: &str,
+) -> BackendResult<Json<TransferRequest>> {
+    let bytes = db.read(Keyspace::TransferRequests, reference).await?;
+
+    Ok(Json(decode(&bytes)?))
+}
+
+/// What the donations selected by `filter` add up to in each token
+#[get("/donations/totals?<filter..>")]
+async fn funding_totals(
+    db: &State<DbState>,
+    filter: DonationFilter,
+) -> BackendResult<Json<Vec<TokenTotal>>> {
+    let mut txn = db.begin_read().await?;
+
+    Ok(Json(donation_totals(&donations_in(&mut txn, &filter)?)))
+}
+
+/// The donors who gave the most among the donations selected by `filter`
+#[get("/donations/supporters?<limit>&<filter..>")]
+async fn funding_supporters(
+    db: &State<DbState>,
+    limit: Option<usize>,
+    filter: DonationFilter,
+) -> BackendResult<Json<Vec<Supporter>>> {
+    let limit = limit
+        .unwrap_or(DEFAULT_SUPPORTERS_LIMIT)
+        .clamp(1, MAX_SUPPORTERS_LIMIT);
+    let mut txn = db.begin_read().await?;
+
+    Ok(Json(top_supporters(
+        &donations_in(&mut txn, &filter)?,
+        limit,
+    )))
+}
@@ -0,0 +1,356 @@
+use ed25519_dalek::VerifyingKey;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use trenchesfund_common::{
+    Donation, Merch, Project, Publisher, PublisherRevision, TransferRequest,
+};
+
+use crate::{BackendError, BackendResult, DbState, Keyspace};
+
+/// Prefix of every value written inside the versioned envelope.
+/// Values without it are legacy records stored as raw bincode (version 0).
+pub const SCHEMA_MAGIC: [u8; 4] = *b"TFSV";
+
+/// Length of the envelope header, the magic followed by a little-endian `u16` version
+const ENVELOPE_HEADER_LEN: usize = SCHEMA_MAGIC.len() + 2;
+
+/// A record stored in the KV that carries a schema version.
+///
+/// To change the layout of a stored type, bump `SCHEMA_VERSION`, keep a frozen
+/// copy of the previous struct and convert it inside `migrate`.
+pub trait Versioned: Serialize + DeserializeOwned {
+    /// The version new records are written with
+    const SCHEMA_VERSION: u16;
+
+    /// Decodes a `payload` written with an older `version`
+    fn migrate(version: u16, payload: &[u8]) -> BackendResult<Self>;
+}
+
+impl Versioned for Project {
+    const SCHEMA_VERSION: u16 = 1;
+
+    fn migrate(version: u16, payload: &[u8]) -> BackendResult<Self> {
+        match version {
+            // Version 1 only introduced the envelope, the layout is unchanged
+            0 => Ok(bincode::deserialize(payload)?),
+            _ => Err(BackendError::UnsupportedSchemaVersion(version)),
+        }
+    }
+}
+
+/// [Publisher] as stored up to version 1, before it had signers
+#[derive(Deserialize)]
+struct PublisherV1 {
+    name: String,
+    icon: String,
+    logo: String,
+    description: String,
+    codebase: String,
+    website: String,
+    public_key: VerifyingKey,
+    mint: VerifyingKey,
+    merch: Vec<Merch>,
+}
+
+impl From<PublisherV1> for Publisher {
+    fn from(publisher: PublisherV1) -> Self {
+        Self {
+            name: publisher.name,
+            icon: publisher.icon,
+            logo: publisher.logo,
+            description: publisher.description,
+            codebase: publisher.codebase,
+            website: publisher.website,
+            public_key: publisher.public_key,
+            mint: publisher.mint,
+            merch: publisher.merch,
+            signers: Vec::default(),
+        }
+    }
+}
+
+impl Versioned for Publisher {
+    const SCHEMA_VERSION: u16 = 2;
+
+    fn migrate(version: u16, payload: &[u8]) -> BackendResult<Self> {
+        match version {
+            // Version 1 only introduced the envelope, version 2 added the signers
+            0 | 1 => Ok(bincode::deserialize::<PublisherV1>(payload)?.into()),
+            _ => Err(BackendError::UnsupportedSchemaVersion(version)),
+        }
+    }
+}
+
+impl Versioned for PublisherRevision {
+    const SCHEMA_VERSION: u16 = 1;
+
+    fn migrate(version: u16, _payload: &[u8]) -> BackendResult<Self> {
+        Err(BackendError::UnsupportedSchemaVersion(version))
+    }
+}
+
+impl Versioned for TransferRequest {
+    const SCHEMA_VERSION: u16 = 1;
+
+    fn migrate(version: u16, _payload: &[u8]) -> BackendResult<Self> {
+        Err(BackendError::UnsupportedSchemaVersion(version))
+    }
+}
+
+impl Versioned for Donation {
+    const SCHEMA_VERSION: u16 = 1;
+
+    fn migrate(version: u16, _payload: &[u8]) -> BackendResult<Self> {
+        Err(BackendError::UnsupportedSchemaVersion(version))
+    }
+}
+
+/// The outcome of upgrading the records of one keyspace to the current schema
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    pub migrated: usize,
+    /// Keys of the records that could not be decoded
+    pub failed: Vec<String>,
+}
+
+/// Brings the stored projects and publishers up to their current schema
+/// versions and moves projects stored under their name to their id
+pub async fn migrate_all(db: &DbState) -> BackendResult<Vec<(Keyspace, MigrationReport)>> {
+    let mut projects = db.migrate_records::<Project>(Keyspace::Projects).await?;
+    let rekeyed = rekey_projects_by_id(db).await?;
+    projects.migrated += rekeyed.migrated;
+    projects.failed.extend(rekeyed.failed);
+
+    Ok(vec![
+        (
+            Keyspace::Publishers,
+            db.migrate_records::<Publisher>(Keyspace::Publishers)
+                .await?,
+        ),
+        (Keyspace::Projects, projects),
+    ])
+}
+
+/// Earlier releases keyed projects by name and trusted the id sent by the
+/// client, such projects are given the id a new project would get
+async fn rekey_projects_by_id(db: &DbState) -> BackendResult<MigrationReport> {
+    let mut txn = db.begin().await?;
+    let mut report = MigrationReport::default();
+
+    for (key, bytes) in txn.entries(Keyspace::Projects)? {
+        // Records that cannot be decoded are reported by the schema migration
+        let Ok(mut project) = decode::<Project>(&bytes) else {
+            continue;
+        };
+
+        if key == project.id_base58() {
+            continue;
+        }
+
+        project.id = Project::derive_id(project.publisher_address(), &project.slug());
+
+        match txn.rekey_indexed(Keyspace::Projects, &key, &project.id_base58(), &project) {
+            Ok(()) => report.migrated += 1,
+            Err(_) => report.failed.push(key),
+        }
+    }
+    txn.commit()?;
+
+    Ok(report)
+}
+
+/// Serializes `record` inside the envelope at its current schema version
+pub fn encode<T: Versioned>(record: &T) -> BackendResult<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(ENVELOPE_HEADER_LEN);
+    bytes.extend_from_slice(&SCHEMA_MAGIC);
+    bytes.extend_from_slice(&T::SCHEMA_VERSION.to_le_bytes());
+    bincode::serialize_into(&mut bytes, record)?;
+
+    Ok(bytes)
+}
+
+/// Deserializes a stored value, migrating it if it was written by an older schema
+pub fn decode<T: Versioned>(bytes: &[u8]) -> BackendResult<T> {
+    let (version, payload) = split_envelope(bytes);
+
+    match version.cmp(&T::SCHEMA_VERSION) {
+        core::cmp::Ordering::Equal => Ok(bincode::deserialize(payload)?),
+        core::cmp::Ordering::Less => T::migrate(version, payload),
+        core::cmp::Ordering::Greater => Err(BackendError::UnsupportedSchemaVersion(version)),
+    }
+}
+
+/// The schema version a stored value was written with
+pub fn stored_version(bytes: &[u8]) -> u16 {
+    split_envelope(bytes).0
+}
+
+fn split_envelope(bytes: &[u8]) -> (u16, &[u8]) {
+    match bytes.strip_prefix(SCHEMA_MAGIC.as_slice()) {
+        Some([low, high, payload @ ..]) => (u16::from_le_bytes([*low, *high]), payload),
+        _ => (0, bytes),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ed25519_dalek::SigningKey;
+    use trenchesfund_common::{
+        CodeLanguage, Phishing, ProjectCategory, PublisherSigner, SignerRole,
+    };
+
+    use super::*;
+
+    const PROJECT_V0: &[u8] = include_bytes!("../fixtures/schema/project_v0.bin");
+    const PROJECT_V1: &[u8] = include_bytes!("../fixtures/schema/project_v1.bin");
+    const PUBLISHER_V0: &[u8] = include_bytes!("../fixtures/schema/publisher_v0.bin");
+    const PUBLISHER_V1: &[u8] = include_bytes!("../fixtures/schema/publisher_v1.bin");
+    const PUBLISHER_V2: &[u8] = include_bytes!("../fixtures/schema/publisher_v2.bin");
+
+    /// The values the fixtures were generated from
+    fn fixture_publisher() -> Publisher {
+        Publisher {
+            name: "Fixture Org".to_string(),
+            icon: "https://example.org/icon.png".to_string(),
+            logo: "https://example.org/logo.png".to_string(),
+            description: "Publisher used by the schema fixtures".to_string(),
+            codebase: "https://example.org/code".to_string(),
+            website: "https://example.org".to_string(),
+            public_key: SigningKey::from_bytes(&[1u8; 32]).verifying_key(),
+            mint: SigningKey::from_bytes(&[2u8; 32]).verifying_key(),
+            merch: vec![Merch {
+                supplier: "Fixture Supplier".to_string(),
+                image: "https://example.org/merch.png".to_string(),
+                name: "Fixture Shirt".to_string(),
+                description: "A shirt".to_string(),
+            }],
+            signers: Vec::default(),
+        }
+    }
+
+    /// The values the version 2 fixture was generated from
+    fn fixture_publisher_v2() -> Publisher {
+        Publisher {
+            signers: vec![PublisherSigner {
+                public_key: SigningKey::from_bytes(&[3u8; 32]).verifying_key(),
+                role: SignerRole::Editor,
+            }],
+            ..fixture_publisher()
+        }
+    }
+
+    fn fixture_project() -> Project {
+        let publisher = fixture_publisher();
+
+        Project {
+            id: blake3::hash(b"fixture-project"),
+            name: "fixture-project".to_string(),
+            logo: "https://example.org/project-logo.png".to_string(),
+            icon: "https://example.org/project-icon.png".to_string(),
+            publisher: (
+                publisher.short_address(),
+                publisher.address(),
+                publisher.name.clone(),
+            ),
+            codebase: "https://example.org/project".to_string(),
+            website: Some("https://example.org/project/site".to_string()),
+            docs: "https://example.org/project/docs".to_string(),
+            phishing: vec![Phishing {
+                name: "fixture-projekt".to_string(),
+                uri: "https://example.com/fixture-projekt".to_string(),
+                analysis: "Typosquat of the project name".to_string(),
+                other_uri: vec![(
+                    "Mirror".to_string(),
+                    "https://example.net/fixture-projekt".to_string(),
+                )],
+            }],
+            category: ProjectCategory::Cryptography,
+            language: CodeLanguage::Rust,
+            package_uri: "https://crates.io/crates/fixture-project".to_string(),
+            description: "Project used by the schema fixtures".to_string(),
+        }
+    }
+
+    #[test]
+    fn decodes_legacy_project() {
+        assert_eq!(stored_version(PROJECT_V0), 0);
+        assert_eq!(decode::<Project>(PROJECT_V0), Ok(fixture_project()));
+    }
+
+    #[test]
+    fn decodes_legacy_publisher() {
+        assert_eq!(stored_version(PUBLISHER_V0), 0);
+        assert_eq!(decode::<Publisher>(PUBLISHER_V0), Ok(fixture_publisher()));
+    }
+
+    #[test]
+    fn round_trips_project_v1() {
+        assert_eq!(stored_version(PROJECT_V1), 1);
+        assert_eq!(decode::<Project>(PROJECT_V1), Ok(fixture_project()));
+        assert_eq!(encode(&fixture_project()).unwrap(), PROJECT_V1);
+    }
+
+    #[test]
+    fn decodes_publisher_v1() {
+        assert_eq!(stored_version(PUBLISHER_V1), 1);
+        assert_eq!(decode::<Publisher>(PUBLISHER_V1), Ok(fixture_publisher()));
+    }
+
+    #[test]
+    fn round_trips_publisher_v2() {
+        assert_eq!(stored_version(PUBLISHER_V2), 2);
+        assert_eq!(
+            decode::<Publisher>(PUBLISHER_V2),
+            Ok(fixture_publisher_v2())
+        );
+        assert_eq!(encode(&fixture_publisher_v2()).unwrap(), PUBLISHER_V2);
+    }
+
+    #[test]
+    fn migrated_records_are_written_at_the_current_version() {
+        let migrated = decode::<Project>(PROJECT_V0).unwrap();
+
+        assert_eq!(encode(&migrated).unwrap(), PROJECT_V1);
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let mut bytes = PROJECT_V1.to_vec();
+        bytes[SCHEMA_MAGIC.len()..ENVELOPE_HEADER_LEN].copy_from_slice(&2u16.to_le_bytes());
+
+        assert_eq!(
+            decode::<Project>(&bytes),
+            Err(BackendError::UnsupportedSchemaVersion(2))
+        );
+    }
+
+    #[rocket::async_test]
+    async fn moves_name_keyed_projects_to_their_id() {
+        let db = DbState::in_memory();
+        let project = fixture_project();
+        db.create(Keyspace::Projects, &project.name, PROJECT_V0)
+            .await
+            .unwrap();
+
+        let reports = migrate_all(&db).await.unwrap();
+        let (_, projects) = reports
+            .into_iter()
+            .find(|(keyspace, _)| *keyspace == Keyspace::Projects)
+            .unwrap();
+        assert_eq!(projects.migrated, 2);
+        assert!(projects.failed.is_empty());
+
+        let id = Project::derive_id(project.publisher_address(), &project.slug());
+        let migrated = decode::<Project>(
+            &db.read(Keyspace::Projects, &Project::id_to_base58(&id))
+                .await
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(migrated, Project { id, ..project });
+
+        assert_eq!(
+            db.read(Keyspace::Projects, "fixture-project").await,
+            Err(BackendError::KvKeyNotFound)
+        );
+    }
+}
@@ -0,0 +1,317 @@
+use std::collections::HashMap;
+
+use trenchesfund_common::{Project, Publisher, SearchResults};
+
+use crate::{decode, BackendResult, DbState, DbTransaction, Keyspace};
+
+const TERM_PREFIX: &[u8] = b"term\0";
+const DOCUMENT_PREFIX: &[u8] = b"doc\0";
+/// How many documents are indexed, kept up to date by every write to the index
+/// so that ranking does not have to count them
+const DOCUMENT_COUNT_KEY: &[u8] = b"count";
+const SEPARATOR: u8 = 0x00;
+
+const NAME_WEIGHT: u32 = 3;
+const PUBLISHER_NAME_WEIGHT: u32 = 2;
+const DESCRIPTION_WEIGHT: u32 = 1;
+/// The last query term also matches terms it is a prefix of, scored lower
+/// than an exact match so that results update while the user is typing
+const PREFIX_MATCH_FACTOR: f64 = 0.5;
+const MIN_TOKEN_LEN: usize = 2;
+const MAX_QUERY_TOKENS: usize = 8;
+
+/// A document in the inverted index
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum SearchDocument {
+    Project(String),
+    Publisher(String),
+}
+
+impl SearchDocument {
+    fn id(&self) -> String {
+        match self {
+            Self::Project(key) => String::from("project:") + key,
+            Self::Publisher(address) => String::from("publisher:") + address,
+        }
+    }
+
+    fn from_id(id: &str) -> Option<Self> {
+        if let Some(key) = id.strip_prefix("project:") {
+            Some(Self::Project(key.to_string()))
+        } else {
+            id.strip_prefix("publisher:")
+                .map(|address| Self::Publisher(address.to_string()))
+        }
+    }
+
+    fn document_key(&self) -> Vec<u8> {
+        [DOCUMENT_PREFIX, self.id().as_bytes()].concat()
+    }
+}
+
+fn term_prefix(token: &str) -> Vec<u8> {
+    [TERM_PREFIX, token.as_bytes(), &[SEPARATOR]].concat()
+}
+
+fn term_key(token: &str, document: &SearchDocument) -> Vec<u8> {
+    [term_prefix(token).as_slice(), document.id().as_bytes()].concat()
+}
+
+/// Lowercases `text` and splits it into alphanumeric tokens
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|character: char| !character.is_alphanumeric())
+        .filter(|token| token.chars().count() >= MIN_TOKEN_LEN)
+        .map(|token| token.to_lowercase())
+        .collect()
+}
+
+fn term_frequencies(fields: &[(&str, u32)]) -> Vec<(String, u32)> {
+    let mut frequencies = HashMap::<String, u32>::new();
+
+    fields.iter().for_each(|(text, weight)| {
+        tokenize(text).into_iter().for_each(|token| {
+            *frequencies.entry(token).or_default() += weight;
+        })
+    });
+
+    let mut frequencies = frequencies.into_iter().collect::<Vec<(String, u32)>>();
+    frequencies.sort();
+
+    frequencies
+}
+
+pub fn index_project(txn: &mut DbTransaction, key: &str, project: &Project) -> BackendResult<()> {
+    let frequencies = term_frequencies(&[
+        (&project.name, NAME_WEIGHT),
+        (project.publisher_name(), PUBLISHER_NAME_WEIGHT),
+        (&project.description, DESCRIPTION_WEIGHT),
+    ]);
+
+    index_document(txn, &SearchDocument::Project(key.to_string()), frequencies)
+}
+
+pub fn index_publisher(txn: &mut DbTransaction, publisher: &Publisher) -> BackendResult<()> {
+    let frequencies = term_frequencies(&[
+        (&publisher.name, NAME_WEIGHT),
+        (&publisher.description, DESCRIPTION_WEIGHT),
+    ]);
+
+    index_document(
+        txn,
+        &SearchDocument::Publisher(publisher.address()),
+        frequencies,
+    )
+}
+
+fn document_count(txn: &mut DbTransaction) -> BackendResult<u64> {
+    let count = txn
+        .get(Keyspace::Search, DOCUMENT_COUNT_KEY)?
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u64::from_le_bytes)
+        .unwrap_or_default();
+
+    Ok(count)
+}
+
+fn set_document_count(txn: &mut DbTransaction, count: u64) -> BackendResult<()> {
+    txn.set(Keyspace::Search, DOCUMENT_COUNT_KEY, &count.to_le_bytes())
+}
+
+/// Replaces the postings of `document`
+fn index_document(
+    txn: &mut DbTransaction,
+    document: &SearchDocument,
+    frequencies: Vec<(String, u32)>,
+) -> BackendResult<()> {
+    // The count only changes for new documents, so that updates do not all
+    // write the same key
+    if !remove_postings(txn, document)? {
+        let count = document_count(txn)?;
+        set_document_count(txn, count + 1)?;
+    }
+
+    for (token, frequency) in &frequencies {
+        txn.set(
+            Keyspace::Search,
+            &term_key(token, document),
+            &frequency.to_le_bytes(),
+        )?;
+    }
+
+    txn.set(
+        Keyspace::Search,
+        &document.document_key(),
+        &bincode::serialize(&frequencies)?,
+    )
+}
+
+pub fn unindex(txn: &mut DbTransaction, document: &SearchDocument) -> BackendResult<()> {
+    if remove_postings(txn, document)? {
+        let count = document_count(txn)?;
+        set_document_count(txn, count.saturating_sub(1))?;
+    }
+
+    Ok(())
+}
+
+/// Deletes the postings of `document`, returning whether it was indexed
+fn remove_postings(txn: &mut DbTransaction, document: &SearchDocument) -> BackendResult<bool> {
+    let Some(bytes) = txn.get(Keyspace::Search, &document.document_key())? else {
+        return Ok(false);
+    };
+
+    for (token, _) in bincode::deserialize::<Vec<(String, u32)>>(&bytes)? {
+        txn.delete(Keyspace::Search, &term_key(&token, document))?;
+    }
+    txn.delete(Keyspace::Search, &document.document_key())?;
+
+    Ok(true)
+}
+
+/// Clears the search keyspace and indexes every stored project and publisher
+/// in one transaction
+pub async fn rebuild_search_index(db: &DbState) -> BackendResult<()> {
+    let mut txn = db.begin().await?;
+
+    for (key, _) in txn.scan_prefix(Keyspace::Search, &[])? {
+        txn.delete(Keyspace::Search, &key)?;
+    }
+
+    for (key, bytes) in txn.entries(Keyspace::Projects)? {
+        if let Ok(project) = decode::<Project>(&bytes) {
+            index_project(&mut txn, &key, &project)?;
+        }
+    }
+
+    for (_, bytes) in txn.entries(Keyspace::Publishers)? {
+        if let Ok(publisher) = decode::<Publisher>(&bytes) {
+            index_publisher(&mut txn, &publisher)?;
+        }
+    }
+
+    txn.commit()
+}
+
+/// Ranks documents by the sum over the query terms of the weighted term
+/// frequency multiplied by the inverse document frequency of the term
+pub async fn search(db: &DbState, query: &str, limit: usize) -> BackendResult<SearchResults> {
+    let mut tokens = tokenize(query);
+    tokens.truncate(MAX_QUERY_TOKENS);
+
+    let mut txn = db.begin_read().await?;
+
+    let total_documents = document_count(&mut txn)?.max(1) as f64;
+    let mut scores = HashMap::<SearchDocument, f64>::new();
+
+    for (position, token) in tokens.iter().enumerate() {
+        let is_last = position == tokens.len() - 1;
+        let prefix = if is_last {
+            [TERM_PREFIX, token.as_bytes()].concat()
+        } else {
+            term_prefix(token)
+        };
+
+        let postings = txn
+            .scan_prefix(Keyspace::Search, &prefix)?
+            .into_iter()
+            .filter_map(|(key, frequency)| {
+                let posting = key.strip_prefix(TERM_PREFIX)?;
+                let separator = posting.iter().position(|byte| *byte == SEPARATOR)?;
+                let exact = &posting[..separator] == token.as_bytes();
+                let document =
+                    SearchDocument::from_id(&String::from_utf8_lossy(&posting[separator + 1..]))?;
+                let frequency = u32::from_le_bytes(frequency.try_into().ok()?);
+
+                Some((document, frequency, exact))
+            })
+            .collect::<Vec<(SearchDocument, u32, bool)>>();
+
+        let inverse_frequency = (1.0 + total_documents / postings.len().max(1) as f64).ln();
+
+        postings
+            .into_iter()
+            .for_each(|(document, frequency, exact)| {
+                let factor = if exact { 1.0 } else { PREFIX_MATCH_FACTOR };
+
+                *scores.entry(document).or_default() +=
+                    frequency as f64 * inverse_frequency * factor;
+            });
+    }
+
+    let mut ranked = scores.into_iter().collect::<Vec<(SearchDocument, f64)>>();
+    ranked.sort_by(|(_, first), (_, second)| second.total_cmp(first));
+
+    let mut results = SearchResults::default();
+
+    for (document, _) in ranked.into_iter().take(limit) {
+        match document {
+            SearchDocument::Project(key) => {
+                if let Ok(bytes) = txn.read(Keyspace::Projects, &key) {
+                    if let Ok(project) = decode::<Project>(&bytes) {
+                        results.projects.push(project);
+                    }
+                }
+            }
+            SearchDocument::Publisher(address) => {
+                if let Ok(bytes) = txn.read(Keyspace::Publishers, &address) {
+                    if let Ok(publisher) = decode::<Publisher>(&bytes) {
+                        results.publishers.push(publisher);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn publisher(name: &str) -> Publisher {
+        Publisher {
+            name: name.to_string(),
+            description: "Keeps the lights on".to_string(),
+            ..Publisher::default()
+        }
+    }
+
+    #[rocket::async_test]
+    async fn counts_indexed_documents() {
+        let db = DbState::in_memory();
+        let mut txn = db.begin().await.unwrap();
+        let first = SearchDocument::Project("first".to_string());
+
+        index_document(&mut txn, &first, term_frequencies(&[("alpha", 1)])).unwrap();
+        index_document(
+            &mut txn,
+            &SearchDocument::Project("second".to_string()),
+            term_frequencies(&[("beta", 1)]),
+        )
+        .unwrap();
+        assert_eq!(document_count(&mut txn).unwrap(), 2);
+
+        // Reindexing a document replaces it
+        index_document(&mut txn, &first, term_frequencies(&[("gamma", 1)])).unwrap();
+        assert_eq!(document_count(&mut txn).unwrap(), 2);
+
+        unindex(&mut txn, &first).unwrap();
+        unindex(&mut txn, &first).unwrap();
+        assert_eq!(document_count(&mut txn).unwrap(), 1);
+        txn.commit().unwrap();
+
+        // Rebuilding counts the stored records only
+        db.create(
+            Keyspace::Publishers,
+            "publisher",
+            &crate::encode(&publisher("Foo")).unwrap(),
+        )
+        .await
+        .unwrap();
+        rebuild_search_index(&db).await.unwrap();
+
+        let mut txn = db.begin_read().await.unwrap();
+        assert_eq!(document_count(&mut txn).unwrap(), 1);
+    }
+}
@@ -0,0 +1,189 @@
+use rand::{rngs::OsRng, RngCore};
+use rocket::{
+    request::{self, FromRequest},
+    Request,
+};
+use trenchesfund_common::{Publisher, Session, SignIn, SignInChallenge};
+
+use crate::{fail, unix_timestamp, BackendError, BackendResult, DbState, Keyspace};
+
+pub const SESSION_COOKIE: &str = "trenches_session";
+
+const SIWS_DOMAIN: &str = "inthetrenches.cloud";
+const CHALLENGE_VALIDITY_SECS: u64 = 300;
+pub const SESSION_VALIDITY_SECS: u64 = 60 * 60 * 24;
+
+/// 32 random bytes in base58, as long as a public key
+pub fn random_base58() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+
+    bs58::encode(&bytes).into_string()
+}
+
+const CHALLENGE_PREFIX: &str = "challenge:";
+const SESSION_PREFIX: &str = "session:";
+
+/// Challenges are keyed by nonce so that issuing one never replaces another
+/// pending challenge of the same address
+fn challenge_key(address: &str, nonce: &str) -> String {
+    String::new() + CHALLENGE_PREFIX + address + ":" + nonce
+}
+
+fn session_key(token: &str) -> String {
+    String::from(SESSION_PREFIX) + token
+}
+
+/// Issues a new challenge for `address`, leaving its other pending challenges in place
+pub async fn issue_challenge(db: &DbState, address: &str) -> BackendResult<SignInChallenge> {
+    Publisher::base58_to_public_key(address).ok_or(BackendError::InvalidPublicKey)?;
+
+    let issued_at = unix_timestamp();
+    let nonce = random_base58();
+    let challenge = SignInChallenge::new(
+        SIWS_DOMAIN,
+        address,
+        &nonce,
+        issued_at,
+        issued_at + CHALLENGE_VALIDITY_SECS,
+    );
+
+    db.create(
+        Keyspace::Sessions,
+        &challenge_key(address, &nonce),
+        &bincode::serialize(&challenge)?,
+    )
+    .await?;
+
+    Ok(challenge)
+}
+
+/// Consumes the challenge answered by the signer and starts a [Session]. The
+/// challenge is only consumed once its signature checks out so that a bogus
+/// answer cannot burn it.
+pub async fn sign_in(db: &DbState, sign_in: &SignIn) -> BackendResult<Session> {
+    let public_key =
+        Publisher::base58_to_public_key(&sign_in.address).ok_or(BackendError::InvalidPublicKey)?;
+
+    let key = challenge_key(&sign_in.address, &sign_in.nonce);
+    let mut txn = db.begin().await?;
+
+    let challenge = match txn.read(Keyspace::Sessions, &key) {
+        Ok(bytes) => bincode::deserialize::<SignInChallenge>(&bytes)?,
+        Err(BackendError::KvKeyNotFound) => return Err(BackendError::Unauthorized),
+        Err(error) => return Err(error),
+    };
+
+    if challenge.expires_at < unix_timestamp() {
+        return Err(BackendError::RequestExpired);
+    }
+
+    public_key
+        .verify_strict(challenge.message.as_bytes(), &sign_in.signature)
+        .or(Err(BackendError::InvalidSignature))?;
+
+    // Answering the same challenge twice conflicts on this delete
+    txn.delete(Keyspace::Sessions, key.as_bytes())?;
+
+    let session = Session {
+        token: random_base58(),
+        address: sign_in.address.clone(),
+        expires_at: unix_timestamp() + SESSION_VALIDITY_SECS,
+    };
+
+    txn.create(
+        Keyspace::Sessions,
+        &session_key(&session.token),
+        &bincode::serialize(&session)?,
+    )?;
+    txn.commit()?;
+
+    Ok(session)
+}
+
+/// Removes the challenges and sessions past their expiry, along with any
+/// entry that no longer decodes
+pub async fn purge_expired_sessions(db: &DbState) -> BackendResult<()> {
+    let now = unix_timestamp();
+    let mut txn = db.begin().await?;
+
+    for (key, value) in txn.entries(Keyspace::Sessions)? {
+        let expires_at = if key.starts_with(CHALLENGE_PREFIX) {
+            bincode::deserialize::<SignInChallenge>(&value)
+                .map(|challenge| challenge.expires_at)
+                .ok()
+        } else if key.starts_with(SESSION_PREFIX) {
+            bincode::deserialize::<Session>(&value)
+                .map(|session| session.expires_at)
+                .ok()
+        } else {
+            None
+        };
+
+        if expires_at.is_none_or(|expires_at| expires_at < now) {
+            txn.delete(Keyspace::Sessions, key.as_bytes())?;
+        }
+    }
+
+    txn.commit()
+}
+
+pub async fn sign_out(db: &DbState, token: &str) -> BackendResult<()> {
+    db.remove(Keyspace::Sessions, &session_key(token)).await
+}
+
+async fn resolve_session(db: &DbState, token: &str) -> BackendResult<Session> {
+    let key = session_key(token);
+
+    let session = match db.read(Keyspace::Sessions, &key).await {
+        Ok(bytes) => bincode::deserialize::<Session>(&bytes)?,
+        Err(BackendError::KvKeyNotFound) => return Err(BackendError::Unauthorized),
+        Err(error) => return Err(error),
+    };
+
+    if session.expires_at < unix_timestamp() {
+        db.purge(Keyspace::Sessions, &key).await?;
+
+        return Err(BackendError::SessionExpired);
+    }
+
+    Ok(session)
+}
+
+/// A request guard for routes that require a signed in wallet. The session
+/// token is read from the `Authorization: Bearer` header or the session cookie
+#[derive(Debug)]
+pub struct AuthenticatedSession(pub Session);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AuthenticatedSession {
+    type Error = BackendError;
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let token = request
+            .headers()
+            .get_one("Authorization")
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(|value| value.to_string())
+            .or_else(|| {
+                request
+                    .cookies()
+                    .get(SESSION_COOKIE)
+                    .map(|cookie| cookie.value().to_string())
+            });
+
+        let Some(token) = token else {
+            return fail(request, BackendError::Unauthorized);
+        };
+
+        let db = match DbState::managed(request) {
+            Ok(db) => db,
+            Err(error) => return fail(request, error),
+        };
+
+        match resolve_session(db, &token).await {
+            Ok(session) => request::Outcome::Success(Self(session)),
+            Err(error) => fail(request, error),
+        }
+    }
+}
@@ -0,0 +1,295 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    ops::Bound,
+    sync::{Mutex, PoisonError},
+};
+
+use async_dup::Arc;
+use async_lock::RwLock;
+use surrealkv::{Durability, Store, Transaction};
+
+use crate::{BackendError, BackendResult, DatabaseConfig, DbTransaction, INDEX_PREFIX};
+
+/// Appended to the directory of a legacy store once it has been imported
+const IMPORTED_EXTENSION: &str = "imported";
+
+/// Record scans stop before the secondary index entries
+pub const RECORDS_END: Bound<&[u8]> = Bound::Excluded(&[INDEX_PREFIX]);
+
+/// A key range for [StorageTransaction::scan]
+pub type KeyRange<'a> = (Bound<&'a [u8]>, Bound<&'a [u8]>);
+
+/// Whether a transaction will write
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+/// A transaction over the raw keys of a store. Writes are only visible to
+/// other transactions once [StorageTransaction::commit] succeeds.
+pub trait StorageTransaction: Send {
+    fn get(&mut self, key: &[u8]) -> BackendResult<Option<Vec<u8>>>;
+
+    fn set(&mut self, key: &[u8], value: &[u8]) -> BackendResult<()>;
+
+    fn delete(&mut self, key: &[u8]) -> BackendResult<()>;
+
+    /// Entries within `range` ordered by key, at most `limit` of them
+    fn scan(
+        &mut self,
+        range: KeyRange<'_>,
+        limit: Option<usize>,
+    ) -> BackendResult<Vec<(Vec<u8>, Vec<u8>)>>;
+
+    fn commit(self: Box<Self>) -> BackendResult<()>;
+}
+
+/// A transactional key-value backend. Datasets are keyspaces within one
+/// store so a single transaction can write to several of them.
+#[rocket::async_trait]
+pub trait Storage: Send + Sync {
+    async fn begin(&self, access: Access) -> BackendResult<Box<dyn StorageTransaction>>;
+}
+
+/// Persists every keyspace in one surrealkv store on disk
+pub struct SurrealKvStorage {
+    store: Arc<RwLock<Store>>,
+    durability: Durability,
+}
+
+impl SurrealKvStorage {
+    /// Opens the store, importing the per-dataset stores of earlier releases
+    pub async fn open(config: &DatabaseConfig) -> BackendResult<Self> {
+        config.ensure_writable()?;
+
+        let storage = Self {
+            store: Arc::new(RwLock::new(Store::new(
+                config.store_options(&config.store),
+            )?)),
+            durability: config.sync.into(),
+        };
+        storage.import_legacy_stores(config).await?;
+
+        Ok(storage)
+    }
+
+    /// Copies each legacy store into its keyspace in one transaction, keeping
+    /// entries already present, then renames its directory so that it is
+    /// only imported once
+    async fn import_legacy_stores(&self, config: &DatabaseConfig) -> BackendResult<()> {
+        for (keyspace, store_name) in config.stores.by_keyspace() {
+            let legacy_dir = config.store_dir(store_name);
+
+            if !legacy_dir.is_dir() {
+                continue;
+            }
+
+            let legacy = Store::new(config.store_options(store_name))?;
+            let entries = StorageTransaction::scan(
+                &mut legacy.begin()?,
+                (Bound::Unbounded, Bound::Unbounded),
+                None,
+            )?;
+            legacy.close()?;
+
+            let mut txn = DbTransaction::new(self.begin(Access::Write).await?);
+            let mut imported = 0usize;
+
+            for (key, value) in entries {
+                if txn.get(keyspace, &key)?.is_none() {
+                    txn.set(keyspace, &key, &value)?;
+                    imported += 1;
+                }
+            }
+            txn.commit()?;
+
+            let imported_dir = legacy_dir.with_extension(IMPORTED_EXTENSION);
+            fs::rename(&legacy_dir, &imported_dir).map_err(|error| {
+                BackendError::DatabaseDirUnwritable(
+                    legacy_dir.display().to_string(),
+                    error.to_string(),
+                )
+            })?;
+
+            println!(
+                "Imported {imported} {keyspace} entries from {}, the old store was moved to {}",
+                legacy_dir.display(),
+                imported_dir.display()
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[rocket::async_trait]
+impl Storage for SurrealKvStorage {
+    async fn begin(&self, access: Access) -> BackendResult<Box<dyn StorageTransaction>> {
+        let txn = match access {
+            Access::Read => self.store.read().await.begin()?,
+            Access::Write => {
+                let mut txn = self.store.write().await.begin()?;
+                txn.set_durability(self.durability);
+
+                txn
+            }
+        };
+
+        Ok(Box::new(txn))
+    }
+}
+
+impl StorageTransaction for Transaction {
+    fn get(&mut self, key: &[u8]) -> BackendResult<Option<Vec<u8>>> {
+        Ok(Transaction::get(self, key)?)
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) -> BackendResult<()> {
+        Ok(Transaction::set(self, key, value)?)
+    }
+
+    fn delete(&mut self, key: &[u8]) -> BackendResult<()> {
+        Ok(Transaction::delete(self, key)?)
+    }
+
+    fn scan(
+        &mut self,
+        range: KeyRange<'_>,
+        limit: Option<usize>,
+    ) -> BackendResult<Vec<(Vec<u8>, Vec<u8>)>> {
+        // surrealkv yields nothing for an excluded start, so start at the
+        // smallest key after it instead
+        let successor;
+        let start = match range.0 {
+            Bound::Excluded(key) => {
+                successor = [key, &[0]].concat();
+                Bound::Included(successor.as_slice())
+            }
+            bound => bound,
+        };
+
+        Transaction::scan(self, (start, range.1), limit)
+            .map(|entry| {
+                entry
+                    .map(|(key, value, _)| (key.to_vec(), value))
+                    .map_err(BackendError::from)
+            })
+            .collect()
+    }
+
+    fn commit(mut self: Box<Self>) -> BackendResult<()> {
+        Ok(Transaction::commit(&mut self)?)
+    }
+}
+
+/// Keeps every keyspace in memory, for tests and throwaway instances
+#[derive(Default)]
+pub struct MemoryStorage {
+    db: std::sync::Arc<Mutex<MemoryDb>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[derive(Default)]
+struct MemoryDb {
+    entries: BTreeMap<Vec<u8>, Vec<u8>>,
+    /// The commit that last wrote each key, so that concurrent writers are detected
+    written_at: HashMap<Vec<u8>, u64>,
+    version: u64,
+}
+
+#[rocket::async_trait]
+impl Storage for MemoryStorage {
+    async fn begin(&self, _access: Access) -> BackendResult<Box<dyn StorageTransaction>> {
+        let (snapshot, version) = {
+            let db = self.db.lock().unwrap_or_else(PoisonError::into_inner);
+            (db.entries.clone(), db.version)
+        };
+
+        Ok(Box::new(MemoryTransaction {
+            db: self.db.clone(),
+            snapshot,
+            version,
+            writes: Vec::default(),
+        }))
+    }
+}
+
+struct MemoryTransaction {
+    db: std::sync::Arc<Mutex<MemoryDb>>,
+    snapshot: BTreeMap<Vec<u8>, Vec<u8>>,
+    version: u64,
+    writes: Vec<(Vec<u8>, Option<Vec<u8>>)>,
+}
+
+impl StorageTransaction for MemoryTransaction {
+    fn get(&mut self, key: &[u8]) -> BackendResult<Option<Vec<u8>>> {
+        Ok(self.snapshot.get(key).cloned())
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) -> BackendResult<()> {
+        self.snapshot.insert(key.to_vec(), value.to_vec());
+        self.writes.push((key.to_vec(), Some(value.to_vec())));
+
+        Ok(())
+    }
+
+    fn delete(&mut self, key: &[u8]) -> BackendResult<()> {
+        self.snapshot.remove(key);
+        self.writes.push((key.to_vec(), None));
+
+        Ok(())
+    }
+
+    fn scan(
+        &mut self,
+        range: KeyRange<'_>,
+        limit: Option<usize>,
+    ) -> BackendResult<Vec<(Vec<u8>, Vec<u8>)>> {
+        let results = self
+            .snapshot
+            .range::<[u8], _>(range)
+            .take(limit.unwrap_or(usize::MAX))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+
+        Ok(results)
+    }
+
+    fn commit(self: Box<Self>) -> BackendResult<()> {
+        if self.writes.is_empty() {
+            return Ok(());
+        }
+
+        let mut db = self.db.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let conflict = self.writes.iter().any(|(key, _)| {
+            db.written_at
+                .get(key)
+                .is_some_and(|written_at| *written_at > self.version)
+        });
+        if conflict {
+            return Err(BackendError::TransactionConflict);
+        }
+
+        db.version += 1;
+        let version = db.version;
+
+        for (key, value) in self.writes {
+            db.written_at.insert(key.clone(), version);
+
+            match value {
+                Some(value) => db.entries.insert(key, value),
+                None => db.entries.remove(&key),
+            };
+        }
+
+        Ok(())
+    }
+}
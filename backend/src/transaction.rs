@@ -0,0 +1,236 @@
+use std::ops::Bound;
+
+use crate::{
+    decode, encode, BackendError, BackendResult, IndexKey, Indexed, KeyRange, Keyspace,
+    StorageTransaction, Versioned, RECORDS_END,
+};
+
+/// The smallest key greater than every key starting with `prefix`
+fn prefix_end(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut end = prefix.to_vec();
+
+    while let Some(last) = end.pop() {
+        if last < u8::MAX {
+            end.push(last + 1);
+
+            return Some(end);
+        }
+    }
+
+    None
+}
+
+/// A transaction spanning every keyspace of the store. Reads see a snapshot
+/// taken when it began and either all of its writes are committed or none are,
+/// so a check followed by a write cannot race another transaction.
+pub struct DbTransaction {
+    inner: Box<dyn StorageTransaction>,
+}
+
+impl DbTransaction {
+    pub fn new(inner: Box<dyn StorageTransaction>) -> Self {
+        Self { inner }
+    }
+
+    pub fn get(&mut self, keyspace: Keyspace, key: &[u8]) -> BackendResult<Option<Vec<u8>>> {
+        self.inner.get(&[&keyspace.prefix(), key].concat())
+    }
+
+    pub fn set(&mut self, keyspace: Keyspace, key: &[u8], value: &[u8]) -> BackendResult<()> {
+        self.inner.set(&[&keyspace.prefix(), key].concat(), value)
+    }
+
+    pub fn delete(&mut self, keyspace: Keyspace, key: &[u8]) -> BackendResult<()> {
+        self.inner.delete(&[&keyspace.prefix(), key].concat())
+    }
+
+    /// Entries of `keyspace` within `range` ordered by key, at most `limit` of them
+    pub fn scan(
+        &mut self,
+        keyspace: Keyspace,
+        range: KeyRange<'_>,
+        limit: Option<usize>,
+    ) -> BackendResult<Vec<(Vec<u8>, Vec<u8>)>> {
+        let prefix = keyspace.prefix();
+        let prefixed = |key: &[u8]| [prefix.as_slice(), key].concat();
+
+        let start = match range.0 {
+            Bound::Included(key) => Bound::Included(prefixed(key)),
+            Bound::Excluded(key) => Bound::Excluded(prefixed(key)),
+            Bound::Unbounded => Bound::Included(prefix.clone()),
+        };
+        let end = match range.1 {
+            Bound::Included(key) => Bound::Included(prefixed(key)),
+            Bound::Excluded(key) => Bound::Excluded(prefixed(key)),
+            Bound::Unbounded => prefix_end(&prefix).map_or(Bound::Unbounded, Bound::Excluded),
+        };
+
+        let results = self
+            .inner
+            .scan(
+                (
+                    start.as_ref().map(Vec::as_slice),
+                    end.as_ref().map(Vec::as_slice),
+                ),
+                limit,
+            )?
+            .into_iter()
+            .map(|(key, value)| (key[prefix.len()..].to_vec(), value))
+            .collect();
+
+        Ok(results)
+    }
+
+    /// All entries of `keyspace` whose key starts with `prefix`
+    pub fn scan_prefix(
+        &mut self,
+        keyspace: Keyspace,
+        prefix: &[u8],
+    ) -> BackendResult<Vec<(Vec<u8>, Vec<u8>)>> {
+        let end = prefix_end(prefix);
+        let end = match end.as_ref() {
+            Some(end) => Bound::Excluded(end.as_slice()),
+            None => Bound::Unbounded,
+        };
+
+        self.scan(keyspace, (Bound::Included(prefix), end), None)
+    }
+
+    /// The records of `keyspace`, leaving out its index entries
+    pub fn entries(&mut self, keyspace: Keyspace) -> BackendResult<Vec<(String, Vec<u8>)>> {
+        let results = self
+            .scan(keyspace, (Bound::Unbounded, RECORDS_END), None)?
+            .into_iter()
+            .map(|(key, value)| (String::from_utf8_lossy(&key).to_string(), value))
+            .collect();
+
+        Ok(results)
+    }
+
+    /// The sorted keys of the records indexed under `index_key`
+    pub fn index_lookup(
+        &mut self,
+        keyspace: Keyspace,
+        index_key: &IndexKey,
+    ) -> BackendResult<Vec<String>> {
+        let results = self
+            .scan_prefix(keyspace, &index_key.prefix())?
+            .into_iter()
+            .filter_map(|(entry, _)| index_key.record_key(&entry))
+            .collect();
+
+        Ok(results)
+    }
+
+    pub fn read(&mut self, keyspace: Keyspace, key: &str) -> BackendResult<Vec<u8>> {
+        self.get(keyspace, key.as_bytes())?
+            .ok_or(BackendError::KvKeyNotFound)
+    }
+
+    /// Inserts `value`, failing if `key` already exists
+    pub fn create(&mut self, keyspace: Keyspace, key: &str, value: &[u8]) -> BackendResult<()> {
+        if self.get(keyspace, key.as_bytes())?.is_some() {
+            return Err(BackendError::KvAlreadyExists);
+        }
+
+        self.set(keyspace, key.as_bytes(), value)
+    }
+
+    /// Replaces `value`, failing if `key` does not exist
+    pub fn update(&mut self, keyspace: Keyspace, key: &str, value: &[u8]) -> BackendResult<()> {
+        self.read(keyspace, key)?;
+
+        self.set(keyspace, key.as_bytes(), value)
+    }
+
+    /// Deletes `key`, failing if it does not exist
+    pub fn remove(&mut self, keyspace: Keyspace, key: &str) -> BackendResult<()> {
+        self.read(keyspace, key)?;
+
+        self.delete(keyspace, key.as_bytes())
+    }
+
+    /// Inserts a record and its index entries
+    pub fn create_indexed<T: Versioned + Indexed>(
+        &mut self,
+        keyspace: Keyspace,
+        key: &str,
+        record: &T,
+    ) -> BackendResult<()> {
+        self.create(keyspace, key, &encode(record)?)?;
+
+        for index_key in record.index_keys() {
+            self.set(keyspace, &index_key.entry(key), &[])?;
+        }
+
+        Ok(())
+    }
+
+    /// Replaces a record and moves its index entries
+    pub fn update_indexed<T: Versioned + Indexed>(
+        &mut self,
+        keyspace: Keyspace,
+        key: &str,
+        record: &T,
+    ) -> BackendResult<()> {
+        self.remove_indexed::<T>(keyspace, key)?;
+
+        self.set(keyspace, key.as_bytes(), &encode(record)?)?;
+        for index_key in record.index_keys() {
+            self.set(keyspace, &index_key.entry(key), &[])?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes a record and its index entries
+    pub fn remove_indexed<T: Versioned + Indexed>(
+        &mut self,
+        keyspace: Keyspace,
+        key: &str,
+    ) -> BackendResult<()> {
+        let current = decode::<T>(&self.read(keyspace, key)?)?;
+
+        for index_key in current.index_keys() {
+            self.delete(keyspace, &index_key.entry(key))?;
+        }
+
+        self.delete(keyspace, key.as_bytes())
+    }
+
+    /// Moves a record to `new_key`, writing `value` in its place
+    pub fn rekey(
+        &mut self,
+        keyspace: Keyspace,
+        old_key: &str,
+        new_key: &str,
+        value: &[u8],
+    ) -> BackendResult<()> {
+        self.remove(keyspace, old_key)?;
+
+        self.create(keyspace, new_key, value)
+    }
+
+    /// Moves a record and its index entries to `new_key`
+    pub fn rekey_indexed<T: Versioned + Indexed>(
+        &mut self,
+        keyspace: Keyspace,
+        old_key: &str,
+        new_key: &str,
+        record: &T,
+    ) -> BackendResult<()> {
+        if self.get(keyspace, new_key.as_bytes())?.is_some() {
+            return Err(BackendError::KvAlreadyExists);
+        }
+
+        self.remove_indexed::<T>(keyspace, old_key)?;
+
+        self.create_indexed(keyspace, new_key, record)
+    }
+
+    /// Fails with [BackendError::TransactionConflict] if another transaction
+    /// committed a write to a key this one also wrote since it began
+    pub fn commit(self) -> BackendResult<()> {
+        self.inner.commit()
+    }
+}
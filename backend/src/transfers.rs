@@ -0,0 +1,305 @@
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+use trenchesfund_common::{
+    DonationRequest, Project, Publisher, TransferRequest, TransferStatus, Validate, AMOUNT_DECIMALS,
+};
+
+use crate::{
+    decode, random_base58, record_donation, BackendError, BackendResult, DbState, DbTransaction,
+    IndexKey, Keyspace,
+};
+
+/// Lamports in a SOL, as a power of ten
+const LAMPORT_DECIMALS: u32 = 9;
+
+const TRANSFER_MESSAGE: &str = "Thanks for buying coffee and keeping the lights on.";
+
+/// Pending transfer requests a publisher can have at once, each one costs an
+/// RPC call on every pass of [confirm_transfers]
+pub const MAX_PENDING_TRANSFERS: usize = 20;
+
+/// Creates a pending transfer request paying the publisher of `request` in its mint
+pub fn request_transfer(
+    txn: &mut DbTransaction,
+    request: &DonationRequest,
+    requested_at: u64,
+) -> BackendResult<TransferRequest> {
+    request.validate()?;
+
+    let publisher = decode::<Publisher>(&txn.read(Keyspace::Publishers, &request.publisher)?)?;
+
+    let pending = txn.index_lookup(
+        Keyspace::TransferRequests,
+        &IndexKey::Publisher(publisher.address()),
+    )?;
+    if pending.len() >= MAX_PENDING_TRANSFERS {
+        return Err(BackendError::TooManyPendingTransfers);
+    }
+
+    if let Some(id) = request.project.as_deref() {
+        let project = decode::<Project>(&txn.read(Keyspace::Projects, id)?)?;
+
+        if project.publisher_address() != request.publisher {
+            return Err(BackendError::BadRequest(
+                "The project is not listed by this publisher".to_string(),
+            ));
+        }
+    }
+
+    let transfer = TransferRequest {
+        reference: random_base58(),
+        recipient: publisher.address(),
+        mint: publisher.mint_address(),
+        amount: request.amount,
+        project: request.project.clone(),
+        label: publisher.name,
+        message: TRANSFER_MESSAGE.to_string(),
+        requested_at,
+        status: TransferStatus::Pending,
+    };
+    txn.create_indexed(Keyspace::TransferRequests, &transfer.reference, &transfer)?;
+
+    Ok(transfer)
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcError {
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse {
+    #[serde(default)]
+    result: Value,
+    error: Option<RpcError>,
+}
+
+/// A signature returned by `getSignaturesForAddress`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureInfo {
+    pub signature: String,
+    /// Set if the transaction failed
+    pub err: Option<Value>,
+}
+
+/// A JSON-RPC client for the Solana endpoint set in [crate::SolanaConfig]
+#[derive(Debug, Clone)]
+pub struct SolanaRpc {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl SolanaRpc {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> BackendResult<T> {
+        let response = self
+            .client
+            .post(&self.url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": method,
+                "params": params,
+            }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<RpcResponse>()
+            .await?;
+
+        if let Some(error) = response.error {
+            return Err(BackendError::Rpc(error.message));
+        }
+
+        serde_json::from_value(response.result)
+            .map_err(|error| BackendError::Rpc(format!("Unexpected `{method}` result: {error}")))
+    }
+
+    /// The confirmed transactions that include `address`, newest first
+    pub async fn signatures_for(&self, address: &str) -> BackendResult<Vec<SignatureInfo>> {
+        self.call(
+            "getSignaturesForAddress",
+            json!([address, { "commitment": "confirmed" }]),
+        )
+        .await
+    }
+
+    /// The parsed transaction behind `signature`, if the endpoint has it
+    pub async fn transaction(&self, signature: &str) -> BackendResult<Option<Value>> {
+        self.call(
+            "getTransaction",
+            json!([signature, {
+                "commitment": "confirmed",
+                "encoding": "jsonParsed",
+                "maxSupportedTransactionVersion": 0,
+            }]),
+        )
+        .await
+    }
+}
+
+/// Scales `amount` in units of `decimals` to billionths of a token
+fn normalize(amount: u128, decimals: u32) -> u128 {
+    if decimals <= AMOUNT_DECIMALS {
+        amount * 10u128.pow(AMOUNT_DECIMALS - decimals)
+    } else {
+        amount / 10u128.pow(decimals - AMOUNT_DECIMALS)
+    }
+}
+
+/// What the recipient of `transfer` received in `transaction`, in billionths of a token
+fn received(transaction: &Value, transfer: &TransferRequest) -> Option<u128> {
+    let meta = &transaction["meta"];
+
+    if transfer.is_native() {
+        let index = transaction["transaction"]["message"]["accountKeys"]
+            .as_array()?
+            .iter()
+            .position(|key| key["pubkey"] == transfer.recipient.as_str())?;
+        let pre = meta["preBalances"][index].as_u64()?;
+        let post = meta["postBalances"][index].as_u64()?;
+
+        return Some(normalize(post.saturating_sub(pre).into(), LAMPORT_DECIMALS));
+    }
+
+    // Token accounts created by the transfer have no balance before it
+    let balance = |balances: &Value| {
+        let mut total = 0u128;
+        let mut decimals = 0u32;
+
+        for balance in balances.as_array().into_iter().flatten() {
+            if balance["owner"] == transfer.recipient.as_str()
+                && balance["mint"] == transfer.mint.as_str()
+            {
+                total += balance["uiTokenAmount"]["amount"]
+                    .as_str()?
+                    .parse::<u128>()
+                    .ok()?;
+                decimals = balance["uiTokenAmount"]["decimals"].as_u64()? as u32;
+            }
+        }
+
+        Some((total, decimals))
+    };
+
+    let (pre, _) = balance(&meta["preTokenBalances"])?;
+    let (post, decimals) = balance(&meta["postTokenBalances"])?;
+
+    Some(normalize(post.saturating_sub(pre), decimals))
+}
+
+/// The first successful transaction referencing `transfer` that paid it in full
+async fn find_payment(
+    rpc: &SolanaRpc,
+    transfer: &TransferRequest,
+    now: u64,
+) -> BackendResult<Option<TransferStatus>> {
+    for info in rpc.signatures_for(&transfer.reference).await? {
+        if info.err.is_some() {
+            continue;
+        }
+
+        let Some(transaction) = rpc.transaction(&info.signature).await? else {
+            continue;
+        };
+
+        if !transaction["meta"]["err"].is_null()
+            || received(&transaction, transfer).unwrap_or_default() < u128::from(transfer.amount)
+        {
+            continue;
+        }
+
+        let donor = transaction["transaction"]["message"]["accountKeys"][0]["pubkey"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+
+        return Ok(Some(TransferStatus::Confirmed {
+            signature: info.signature,
+            donor,
+            confirmed_at: transaction["blockTime"].as_u64().unwrap_or(now),
+        }));
+    }
+
+    Ok(None)
+}
+
+/// Checks every pending transfer request against the RPC, confirming the paid
+/// ones into the donation ledger and dropping those unpaid after
+/// `validity_secs`. A request the RPC fails to answer for is checked again on
+/// the next pass. Confirmed requests are dropped `validity_secs` after their
+/// payment, the ledger keeps the donation. Returns the number of requests confirmed.
+pub async fn confirm_transfers(
+    db: &DbState,
+    rpc: &SolanaRpc,
+    now: u64,
+    validity_secs: u64,
+) -> BackendResult<usize> {
+    let transfers = db
+        .values(Keyspace::TransferRequests)
+        .await?
+        .iter()
+        .filter_map(|bytes| decode::<TransferRequest>(bytes).ok())
+        .collect::<Vec<TransferRequest>>();
+
+    let mut confirmed = 0usize;
+
+    for transfer in transfers {
+        // Donors polling the request still see it confirmed for a while
+        if let TransferStatus::Confirmed { confirmed_at, .. } = transfer.status {
+            if confirmed_at.saturating_add(validity_secs) < now {
+                db.remove_indexed::<TransferRequest>(
+                    Keyspace::TransferRequests,
+                    &transfer.reference,
+                )
+                .await?;
+            }
+            continue;
+        }
+
+        let payment = match find_payment(rpc, &transfer, now).await {
+            Ok(payment) => payment,
+            Err(error) => {
+                eprintln!(
+                    "Unable to check the transfer request {}: {error}",
+                    transfer.reference
+                );
+                continue;
+            }
+        };
+
+        match payment {
+            Some(status) => {
+                let mut txn = db.begin().await?;
+                let mut stored = decode::<TransferRequest>(
+                    &txn.read(Keyspace::TransferRequests, &transfer.reference)?,
+                )?;
+
+                if !stored.is_confirmed() {
+                    stored.status = status;
+                    txn.update_indexed(Keyspace::TransferRequests, &transfer.reference, &stored)?;
+                    record_donation(&mut txn, &stored)?;
+                    txn.commit()?;
+                    confirmed += 1;
+                }
+            }
+            None if transfer.requested_at.saturating_add(validity_secs) < now => {
+                db.remove_indexed::<TransferRequest>(
+                    Keyspace::TransferRequests,
+                    &transfer.reference,
+                )
+                .await?;
+            }
+            None => (),
+        }
+    }
+
+    Ok(confirmed)
+}
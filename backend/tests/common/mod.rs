@@ -0,0 +1,27 @@
+// Every test crate includes this module but only uses some of its helpers
+#![allow(dead_code)]
+
+use rocket::local::asynchronous::Client;
+use trenchesfund_backend::{mount_api, DatabaseConfig, DbState};
+
+/// A client for the API backed by a fresh in-memory store, along with a
+/// handle to that store for seeding records directly
+pub async fn client() -> (Client, DbState) {
+    let db = DbState::in_memory();
+    let client = Client::tracked(mount_api(rocket::build(), db.clone()))
+        .await
+        .expect("valid rocket instance");
+
+    (client, db)
+}
+
+/// A configuration for an empty store in its own temporary directory
+pub fn scratch_config(name: &str) -> DatabaseConfig {
+    let dir = std::env::temp_dir().join(format!("trenchesfund-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+
+    DatabaseConfig {
+        dir,
+        ..DatabaseConfig::default()
+    }
+}
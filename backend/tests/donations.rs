@@ -0,0 +1,139 @@
+mod common;
+
+use rocket::{http::Status, local::asynchronous::Client};
+use serde::de::DeserializeOwned;
+use trenchesfund_backend::{relink_donations, DbState, Keyspace};
+use trenchesfund_common::{Donation, Supporter, TokenTotal};
+
+use common::client;
+
+const TOKEN: u64 = 1_000_000_000;
+
+fn donation(
+    signature: &str,
+    donor: &str,
+    publisher: &str,
+    project: Option<&str>,
+    mint: &str,
+    amount: u64,
+    donated_at: u64,
+) -> Donation {
+    Donation {
+        signature: signature.to_string(),
+        donor: donor.to_string(),
+        publisher: publisher.to_string(),
+        project: project.map(str::to_string),
+        mint: mint.to_string(),
+        amount,
+        donated_at,
+    }
+}
+
+/// Publisher `A` received tokens `X` and `Y`, publisher `B` only `X`
+async fn seed(db: &DbState) {
+    for donation in [
+        donation("S1", "D1", "A", Some("P1"), "X", TOKEN, 100),
+        donation("S2", "D2", "A", None, "X", 2 * TOKEN, 200),
+        donation("S3", "D1", "A", None, "Y", 5 * TOKEN, 300),
+        donation("S4", "D1", "B", Some("P2"), "X", 7 * TOKEN, 150),
+    ] {
+        db.create_indexed(Keyspace::Donations, &donation.signature, &donation)
+            .await
+            .unwrap();
+    }
+}
+
+async fn get<T: DeserializeOwned + Send + 'static>(client: &Client, uri: &str) -> T {
+    let response = client.get(uri.to_string()).dispatch().await;
+    assert_eq!(response.status(), Status::Ok);
+
+    response.into_json::<T>().await.expect("JSON body")
+}
+
+fn total(mint: &str, amount: u64, donations: u64) -> TokenTotal {
+    TokenTotal {
+        mint: mint.to_string(),
+        amount,
+        donations,
+    }
+}
+
+fn supporter(donor: &str, mint: &str, amount: u64, donations: u64) -> Supporter {
+    Supporter {
+        donor: donor.to_string(),
+        mint: mint.to_string(),
+        amount,
+        donations,
+    }
+}
+
+#[rocket::async_test]
+async fn totals_donations_per_publisher_project_and_window() {
+    let (client, db) = client().await;
+    seed(&db).await;
+
+    assert_eq!(
+        get::<Vec<TokenTotal>>(&client, "/donations/totals?publisher=A").await,
+        vec![total("X", 3 * TOKEN, 2), total("Y", 5 * TOKEN, 1)]
+    );
+    assert_eq!(
+        get::<Vec<TokenTotal>>(&client, "/donations/totals?publisher=A&from=150&to=300").await,
+        vec![total("X", 2 * TOKEN, 1)]
+    );
+    assert_eq!(
+        get::<Vec<TokenTotal>>(&client, "/donations/totals?project=P1").await,
+        vec![total("X", TOKEN, 1)]
+    );
+    assert_eq!(
+        get::<Vec<TokenTotal>>(&client, "/donations/totals").await,
+        vec![total("X", 10 * TOKEN, 3), total("Y", 5 * TOKEN, 1)]
+    );
+    assert!(
+        get::<Vec<TokenTotal>>(&client, "/donations/totals?publisher=C")
+            .await
+            .is_empty()
+    );
+}
+
+#[rocket::async_test]
+async fn ranks_the_top_supporters() {
+    let (client, db) = client().await;
+    seed(&db).await;
+
+    assert_eq!(
+        get::<Vec<Supporter>>(&client, "/donations/supporters?publisher=A").await,
+        vec![
+            supporter("D1", "Y", 5 * TOKEN, 1),
+            supporter("D2", "X", 2 * TOKEN, 1),
+            supporter("D1", "X", TOKEN, 1),
+        ]
+    );
+    assert_eq!(
+        get::<Vec<Supporter>>(&client, "/donations/supporters?limit=1").await,
+        vec![supporter("D1", "X", 8 * TOKEN, 2)]
+    );
+    assert_eq!(
+        get::<Vec<Supporter>>(&client, "/donations/supporters?publisher=A&to=200").await,
+        vec![supporter("D1", "X", TOKEN, 1)]
+    );
+}
+
+#[rocket::async_test]
+async fn relinks_donations_to_a_rotated_publisher() {
+    let (client, db) = client().await;
+    seed(&db).await;
+
+    let mut txn = db.begin().await.unwrap();
+    relink_donations(&mut txn, "A", "C").unwrap();
+    txn.commit().unwrap();
+
+    assert!(
+        get::<Vec<TokenTotal>>(&client, "/donations/totals?publisher=A")
+            .await
+            .is_empty()
+    );
+    assert_eq!(
+        get::<Vec<TokenTotal>>(&client, "/donations/totals?publisher=C").await,
+        vec![total("X", 3 * TOKEN, 2), total("Y", 5 * TOKEN, 1)]
+    );
+}
@@ -0,0 +1,1524 @@
+mod common;
+
+use ed25519_dalek::{Signer, SigningKey};
+use rocket::{
+    http::{ContentType, Header, Status},
+    local::asynchronous::{Client, LocalResponse},
+};
+use serde::de::DeserializeOwned;
+use trenchesfund_backend::{
+    encode, insert_project, insert_publisher, move_publisher, purge_expired_sessions,
+    record_revision, unix_timestamp, BackendError, Keyspace,
+};
+use trenchesfund_common::{
+    ApiError, CodeLanguage, ErrorCode, FieldError, KeyRotation, Onboarding, Project,
+    ProjectCategory, ProjectPage, Publisher, PublisherRevision, PublisherSigner, SearchResults,
+    Session, SignIn, SignInChallenge, SignedHeaders, SignedRequest, SignerRole,
+};
+
+use common::client;
+
+fn signing_key(seed: u8) -> SigningKey {
+    SigningKey::from_bytes(&[seed; 32])
+}
+
+fn publisher_for(signing_key: &SigningKey) -> Publisher {
+    Publisher {
+        public_key: signing_key.verifying_key(),
+        ..Publisher::default()
+    }
+}
+
+/// A project carrying the id the backend derives when it is created
+fn project_for(publisher: &Publisher, name: &str) -> Project {
+    Project {
+        id: Project::derive_id(&publisher.address(), &Project::slugify(name)),
+        name: name.to_string(),
+        publisher: (
+            publisher.short_address(),
+            publisher.address(),
+            publisher.name.clone(),
+        ),
+        ..Project::default()
+    }
+}
+
+/// Signs `payload` with a nonce derived from `nonce` at the current time
+fn signed<T: serde::Serialize>(payload: T, signing_key: &SigningKey, nonce: u8) -> String {
+    signed_at(payload, signing_key, nonce, unix_timestamp())
+}
+
+fn signed_at<T: serde::Serialize>(
+    payload: T,
+    signing_key: &SigningKey,
+    nonce: u8,
+    timestamp: u64,
+) -> String {
+    let request = SignedRequest::sign(payload, signing_key, [nonce; 32], timestamp).unwrap();
+
+    serde_json::to_string(&request).unwrap()
+}
+
+fn path_segment(value: &str) -> String {
+    value.replace(' ', "%20")
+}
+
+async fn post_json<'c>(client: &'c Client, uri: &str, body: String) -> LocalResponse<'c> {
+    client
+        .post(uri.to_string())
+        .header(ContentType::JSON)
+        .body(body)
+        .dispatch()
+        .await
+}
+
+async fn json<T: DeserializeOwned + Send + 'static>(response: LocalResponse<'_>) -> T {
+    response.into_json::<T>().await.expect("JSON body")
+}
+
+async fn assert_error(response: LocalResponse<'_>, status: Status, code: ErrorCode) {
+    assert_eq!(response.status(), status);
+    assert_eq!(json::<ApiError>(response).await.code, code);
+}
+
+async fn register(client: &Client, signing_key: &SigningKey, nonce: u8) -> Publisher {
+    let publisher = publisher_for(signing_key);
+    let response = post_json(
+        client,
+        "/publishers",
+        signed(publisher.clone(), signing_key, nonce),
+    )
+    .await;
+
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(json::<String>(response).await, publisher.address());
+
+    publisher
+}
+
+async fn create(client: &Client, project: &Project, signing_key: &SigningKey, nonce: u8) {
+    let response = post_json(
+        client,
+        "/project-create",
+        signed(project.clone(), signing_key, nonce),
+    )
+    .await;
+
+    assert_eq!(response.status(), Status::Ok);
+}
+
+#[rocket::async_test]
+async fn unknown_routes_return_a_json_not_found() {
+    let (client, _) = client().await;
+
+    let response = client.get("/does-not-exist").dispatch().await;
+    assert_error(response, Status::NotFound, ErrorCode::NotFound).await;
+}
+
+#[rocket::async_test]
+async fn registers_a_publisher() {
+    let (client, _) = client().await;
+    let owner = signing_key(1);
+
+    let publisher = register(&client, &owner, 1).await;
+
+    let response = client
+        .post(format!("/publisher-info/{}", publisher.address()))
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(json::<Publisher>(response).await, publisher);
+}
+
+#[rocket::async_test]
+async fn rejects_a_duplicate_publisher() {
+    let (client, _) = client().await;
+    let owner = signing_key(1);
+
+    let publisher = register(&client, &owner, 1).await;
+
+    let response = post_json(&client, "/publishers", signed(publisher, &owner, 2)).await;
+    assert_error(response, Status::Conflict, ErrorCode::AlreadyExists).await;
+}
+
+#[rocket::async_test]
+async fn rejects_a_replayed_request() {
+    let (client, _) = client().await;
+    let owner = signing_key(1);
+    let body = signed(publisher_for(&owner), &owner, 1);
+
+    let response = post_json(&client, "/publishers", body.clone()).await;
+    assert_eq!(response.status(), Status::Ok);
+
+    let response = post_json(&client, "/publishers", body).await;
+    assert_error(response, Status::Unauthorized, ErrorCode::NonceReused).await;
+}
+
+#[rocket::async_test]
+async fn rejects_an_expired_request() {
+    let (client, _) = client().await;
+    let owner = signing_key(1);
+    let body = signed_at(publisher_for(&owner), &owner, 1, unix_timestamp() - 3600);
+
+    let response = post_json(&client, "/publishers", body).await;
+    assert_error(response, Status::Unauthorized, ErrorCode::RequestExpired).await;
+}
+
+#[rocket::async_test]
+async fn rejects_a_tampered_request() {
+    let (client, _) = client().await;
+    let owner = signing_key(1);
+
+    let mut request =
+        SignedRequest::sign(publisher_for(&owner), &owner, [1; 32], unix_timestamp()).unwrap();
+    request.payload.name = "Someone Else".to_string();
+
+    let response = post_json(
+        &client,
+        "/publishers",
+        serde_json::to_string(&request).unwrap(),
+    )
+    .await;
+    assert_error(response, Status::Unauthorized, ErrorCode::InvalidSignature).await;
+}
+
+#[rocket::async_test]
+async fn rejects_registering_another_key() {
+    let (client, _) = client().await;
+
+    let body = signed(publisher_for(&signing_key(1)), &signing_key(2), 1);
+
+    let response = post_json(&client, "/publishers", body).await;
+    assert_error(response, Status::Forbidden, ErrorCode::PermissionDenied).await;
+}
+
+#[rocket::async_test]
+async fn missing_records_are_not_found() {
+    let (client, _) = client().await;
+
+    let response = client.post("/publisher-info/missing").dispatch().await;
+    assert_error(response, Status::NotFound, ErrorCode::NotFound).await;
+
+    let response = client.post("/project-info/missing").dispatch().await;
+    assert_error(response, Status::NotFound, ErrorCode::NotFound).await;
+}
+
+#[rocket::async_test]
+async fn creates_updates_and_removes_a_project() {
+    let (client, _) = client().await;
+    let owner = signing_key(1);
+    let publisher = register(&client, &owner, 1).await;
+
+    let mut project = project_for(&publisher, "Bar Project");
+    create(&client, &project, &owner, 2).await;
+
+    let uri = format!("/project-info/{}", project.id_base58());
+    let response = client.post(uri.clone()).dispatch().await;
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(json::<Project>(response).await, project);
+
+    project.description = "Updated description".to_string();
+    let response = post_json(
+        &client,
+        "/project-update",
+        signed(project.clone(), &owner, 3),
+    )
+    .await;
+    assert_eq!(response.status(), Status::Ok);
+
+    let response = client.post(uri.clone()).dispatch().await;
+    assert_eq!(
+        json::<Project>(response).await.description,
+        project.description
+    );
+
+    let response = post_json(
+        &client,
+        "/project-remove",
+        signed(project.id_base58(), &owner, 4),
+    )
+    .await;
+    assert_eq!(response.status(), Status::Ok);
+
+    let response = client.post(uri).dispatch().await;
+    assert_error(response, Status::NotFound, ErrorCode::NotFound).await;
+}
+
+#[rocket::async_test]
+async fn rejects_a_duplicate_project() {
+    let (client, _) = client().await;
+    let owner = signing_key(1);
+    let publisher = register(&client, &owner, 1).await;
+    let project = project_for(&publisher, "Bar Project");
+
+    create(&client, &project, &owner, 2).await;
+
+    let response = post_json(&client, "/project-create", signed(project, &owner, 3)).await;
+    assert_error(response, Status::Conflict, ErrorCode::AlreadyExists).await;
+}
+
+#[rocket::async_test]
+async fn updating_a_missing_project_is_not_found() {
+    let (client, _) = client().await;
+    let owner = signing_key(1);
+    let publisher = register(&client, &owner, 1).await;
+
+    let body = signed(project_for(&publisher, "Missing"), &owner, 2);
+
+    let response = post_json(&client, "/project-update", body).await;
+    assert_error(response, Status::NotFound, ErrorCode::NotFound).await;
+}
+
+#[rocket::async_test]
+async fn only_the_owner_can_change_a_project() {
+    let (client, _) = client().await;
+    let owner = signing_key(1);
+    let intruder = signing_key(2);
+    let publisher = register(&client, &owner, 1).await;
+    register(&client, &intruder, 1).await;
+
+    let project = project_for(&publisher, "Bar Project");
+
+    let response = post_json(
+        &client,
+        "/project-create",
+        signed(project.clone(), &intruder, 2),
+    )
+    .await;
+    assert_error(response, Status::Forbidden, ErrorCode::PermissionDenied).await;
+
+    create(&client, &project, &owner, 2).await;
+
+    let response = post_json(
+        &client,
+        "/project-update",
+        signed(project.clone(), &intruder, 3),
+    )
+    .await;
+    assert_error(response, Status::Forbidden, ErrorCode::PermissionDenied).await;
+
+    let response = post_json(
+        &client,
+        "/project-remove",
+        signed(project.id_base58(), &intruder, 4),
+    )
+    .await;
+    assert_error(response, Status::Forbidden, ErrorCode::PermissionDenied).await;
+}
+
+#[rocket::async_test]
+async fn unregistered_publishers_cannot_create_projects() {
+    let (client, _) = client().await;
+    let stranger = signing_key(3);
+    let project = project_for(&publisher_for(&stranger), "Bar Project");
+
+    let response = post_json(&client, "/project-create", signed(project, &stranger, 1)).await;
+    assert_error(response, Status::Forbidden, ErrorCode::PermissionDenied).await;
+}
+
+#[rocket::async_test]
+async fn lists_and_filters_projects() {
+    let (client, _) = client().await;
+    let owner = signing_key(1);
+    let publisher = register(&client, &owner, 1).await;
+
+    let rust = Project {
+        language: CodeLanguage::Rust,
+        category: ProjectCategory::Cryptography,
+        ..project_for(&publisher, "alpha")
+    };
+    let go = Project {
+        language: CodeLanguage::Go,
+        category: ProjectCategory::Wallet,
+        ..project_for(&publisher, "beta")
+    };
+    create(&client, &rust, &owner, 2).await;
+    create(&client, &go, &owner, 3).await;
+
+    let mut all = vec![rust.clone(), go.clone()];
+    all.sort_by_key(Project::id_base58);
+
+    let response = client.get("/projects").dispatch().await;
+    let page = json::<ProjectPage>(response).await;
+    assert_eq!(page.projects, all);
+    assert_eq!(page.next_cursor, None);
+
+    let response = client.get("/projects?language=Rust").dispatch().await;
+    assert_eq!(
+        json::<ProjectPage>(response).await.projects,
+        vec![rust.clone()]
+    );
+
+    let response = client
+        .get("/projects?category=Wallet&language=Go")
+        .dispatch()
+        .await;
+    assert_eq!(
+        json::<ProjectPage>(response).await.projects,
+        vec![go.clone()]
+    );
+
+    let response = client
+        .get("/projects?category=Wallet&language=Rust")
+        .dispatch()
+        .await;
+    assert!(json::<ProjectPage>(response).await.projects.is_empty());
+
+    let response = client
+        .get(format!("/projects?publisher={}", publisher.address()))
+        .dispatch()
+        .await;
+    assert_eq!(json::<ProjectPage>(response).await.projects.len(), 2);
+
+    let response = client.get("/projects?category=Unknown").dispatch().await;
+    assert_error(response, Status::BadRequest, ErrorCode::BadRequest).await;
+}
+
+#[rocket::async_test]
+async fn paginates_projects() {
+    let (client, _) = client().await;
+    let owner = signing_key(1);
+    let publisher = register(&client, &owner, 1).await;
+
+    for (nonce, name) in [(2, "alpha"), (3, "beta"), (4, "gamma")] {
+        create(&client, &project_for(&publisher, name), &owner, nonce).await;
+    }
+
+    let mut names = Vec::<String>::new();
+    let mut uri = "/projects?limit=2".to_string();
+
+    loop {
+        let page = json::<ProjectPage>(client.get(uri.clone()).dispatch().await).await;
+        assert!(page.projects.len() <= 2);
+        names.extend(page.projects.into_iter().map(|project| project.name));
+
+        match page.next_cursor {
+            Some(cursor) => uri = format!("/projects?limit=2&cursor={cursor}"),
+            None => break,
+        }
+    }
+
+    names.sort();
+    assert_eq!(names, vec!["alpha", "beta", "gamma"]);
+}
+
+#[rocket::async_test]
+async fn ends_pagination_on_the_last_full_page() {
+    let (client, _) = client().await;
+    let owner = signing_key(1);
+    let publisher = register(&client, &owner, 1).await;
+
+    for (nonce, name) in [(2, "alpha"), (3, "beta")] {
+        create(&client, &project_for(&publisher, name), &owner, nonce).await;
+    }
+
+    for uri in [
+        "/projects?limit=2".to_string(),
+        format!("/projects?limit=2&publisher={}", publisher.address()),
+    ] {
+        let page = json::<ProjectPage>(client.get(uri).dispatch().await).await;
+        assert_eq!(page.projects.len(), 2);
+        assert_eq!(page.next_cursor, None);
+    }
+
+    for uri in [
+        "/projects?limit=1".to_string(),
+        format!("/projects?limit=1&publisher={}", publisher.address()),
+    ] {
+        let page = json::<ProjectPage>(client.get(uri.clone()).dispatch().await).await;
+        let cursor = page.next_cursor.expect("a second page");
+
+        let page = json::<ProjectPage>(
+            client
+                .get(format!("{uri}&cursor={cursor}"))
+                .dispatch()
+                .await,
+        )
+        .await;
+        assert_eq!(page.projects.len(), 1);
+        assert_eq!(page.next_cursor, None);
+    }
+}
+
+#[rocket::async_test]
+async fn skips_corrupt_records() {
+    let (client, db) = client().await;
+    let owner = signing_key(1);
+    let publisher = register(&client, &owner, 1).await;
+    let project = project_for(&publisher, "alpha");
+    create(&client, &project, &owner, 2).await;
+
+    let corrupt = Project::id_to_base58(&blake3::hash(b"corrupt"));
+    db.create(Keyspace::Projects, &corrupt, b"not bincode")
+        .await
+        .unwrap();
+
+    let response = client.get("/projects").dispatch().await;
+    let page = json::<ProjectPage>(response).await;
+    assert_eq!(page.projects, vec![project]);
+    assert_eq!(page.skipped, vec![corrupt.clone()]);
+
+    let response = client
+        .post(format!("/project-info/{corrupt}"))
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::InternalServerError);
+    let error = json::<ApiError>(response).await;
+    assert_eq!(error.code, ErrorCode::Internal);
+    assert_eq!(error.message, "Internal Server Error");
+}
+
+#[rocket::async_test]
+async fn reads_seeded_default_fixtures() {
+    let (client, db) = client().await;
+    let publisher = Publisher::default();
+    let project = Project::default();
+
+    db.create(
+        Keyspace::Publishers,
+        &publisher.address(),
+        &encode(&publisher).unwrap(),
+    )
+    .await
+    .unwrap();
+    db.create_indexed(Keyspace::Projects, &project.id_base58(), &project)
+        .await
+        .unwrap();
+
+    let response = client
+        .post(format!("/publisher-info/{}", publisher.address()))
+        .dispatch()
+        .await;
+    assert_eq!(json::<Publisher>(response).await, publisher);
+
+    let response = client
+        .post(format!("/project-info/{}", project.id_base58()))
+        .dispatch()
+        .await;
+    assert_eq!(json::<Project>(response).await, project);
+
+    let response = client
+        .get("/projects?category=Unspecified")
+        .dispatch()
+        .await;
+    assert_eq!(json::<ProjectPage>(response).await.projects, vec![project]);
+}
+
+#[rocket::async_test]
+async fn searches_projects_and_publishers() {
+    let (client, _) = client().await;
+    let owner = signing_key(1);
+    let publisher = register(&client, &owner, 1).await;
+    let project = Project {
+        description: "Threshold signatures for wallets".to_string(),
+        ..project_for(&publisher, "frost")
+    };
+    create(&client, &project, &owner, 2).await;
+
+    let response = client.get("/search?q=threshold").dispatch().await;
+    let results = json::<SearchResults>(response).await;
+    assert_eq!(results.projects, vec![project.clone()]);
+
+    let response = client.get("/search?q=foo%20org").dispatch().await;
+    assert_eq!(
+        json::<SearchResults>(response).await.publishers,
+        vec![publisher]
+    );
+
+    post_json(
+        &client,
+        "/project-remove",
+        signed(project.id_base58(), &owner, 3),
+    )
+    .await;
+
+    let response = client.get("/search?q=threshold").dispatch().await;
+    assert!(json::<SearchResults>(response).await.projects.is_empty());
+}
+
+#[rocket::async_test]
+async fn signs_in_and_out() {
+    let (client, _) = client().await;
+    let wallet = signing_key(5);
+    let address = Publisher::public_key_to_base58(&wallet.verifying_key());
+
+    let response = client
+        .post(format!("/auth/challenge/{address}"))
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+    let challenge = json::<SignInChallenge>(response).await;
+
+    let sign_in = SignIn {
+        address: address.clone(),
+        nonce: challenge.nonce.clone(),
+        signature: wallet.sign(challenge.message.as_bytes()),
+    };
+    let response = post_json(
+        &client,
+        "/auth/login",
+        serde_json::to_string(&sign_in).unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), Status::Ok);
+    let session = json::<Session>(response).await;
+    assert_eq!(session.address, address);
+
+    let bearer = Header::new("Authorization", format!("Bearer {}", session.token));
+
+    let response = client
+        .post("/auth/session")
+        .header(bearer.clone())
+        .dispatch()
+        .await;
+    assert_eq!(json::<Session>(response).await, session);
+
+    let response = client
+        .post("/auth/logout")
+        .header(bearer.clone())
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+
+    let response = client.post("/auth/session").header(bearer).dispatch().await;
+    assert_error(response, Status::Unauthorized, ErrorCode::Unauthorized).await;
+}
+
+#[rocket::async_test]
+async fn rejects_bad_sign_ins() {
+    let (client, _) = client().await;
+    let wallet = signing_key(5);
+    let address = Publisher::public_key_to_base58(&wallet.verifying_key());
+
+    let response = client
+        .post("/auth/challenge/not-an-address")
+        .dispatch()
+        .await;
+    assert_error(response, Status::BadRequest, ErrorCode::InvalidPublicKey).await;
+
+    let response = client.post("/auth/session").dispatch().await;
+    assert_error(response, Status::Unauthorized, ErrorCode::Unauthorized).await;
+
+    let response = client
+        .post(format!("/auth/challenge/{address}"))
+        .dispatch()
+        .await;
+    let challenge = json::<SignInChallenge>(response).await;
+
+    let sign_in = SignIn {
+        address: address.clone(),
+        nonce: challenge.nonce.clone(),
+        signature: signing_key(6).sign(b"some other message"),
+    };
+    let response = post_json(
+        &client,
+        "/auth/login",
+        serde_json::to_string(&sign_in).unwrap(),
+    )
+    .await;
+    assert_error(response, Status::Unauthorized, ErrorCode::InvalidSignature).await;
+
+    let sign_in = SignIn {
+        nonce: "unknown".to_string(),
+        signature: wallet.sign(challenge.message.as_bytes()),
+        ..sign_in
+    };
+    let response = post_json(
+        &client,
+        "/auth/login",
+        serde_json::to_string(&sign_in).unwrap(),
+    )
+    .await;
+    assert_error(response, Status::Unauthorized, ErrorCode::Unauthorized).await;
+}
+
+async fn request_challenge(client: &Client, address: &str) -> SignInChallenge {
+    let response = client
+        .post(format!("/auth/challenge/{address}"))
+        .dispatch()
+        .await;
+
+    json::<SignInChallenge>(response).await
+}
+
+async fn log_in(client: &Client, sign_in: &SignIn) -> Status {
+    post_json(
+        client,
+        "/auth/login",
+        serde_json::to_string(sign_in).unwrap(),
+    )
+    .await
+    .status()
+}
+
+#[rocket::async_test]
+async fn others_cannot_burn_a_pending_challenge() {
+    let (client, _) = client().await;
+    let wallet = signing_key(5);
+    let address = Publisher::public_key_to_base58(&wallet.verifying_key());
+
+    let pending = request_challenge(&client, &address).await;
+
+    // Someone else asks for a challenge of the same address and answers it wrongly
+    let other = request_challenge(&client, &address).await;
+    assert_ne!(other.nonce, pending.nonce);
+    let bogus = SignIn {
+        address: address.clone(),
+        nonce: pending.nonce.clone(),
+        signature: signing_key(6).sign(pending.message.as_bytes()),
+    };
+    assert_eq!(log_in(&client, &bogus).await, Status::Unauthorized);
+
+    let sign_in = SignIn {
+        address: address.clone(),
+        nonce: pending.nonce.clone(),
+        signature: wallet.sign(pending.message.as_bytes()),
+    };
+    assert_eq!(log_in(&client, &sign_in).await, Status::Ok);
+
+    // A challenge only starts one session
+    assert_eq!(log_in(&client, &sign_in).await, Status::Unauthorized);
+}
+
+#[rocket::async_test]
+async fn purges_expired_challenges_and_sessions() {
+    let (_, db) = client().await;
+    let expired = SignInChallenge::new("domain", "address", "old", 0, 1);
+    let pending = SignInChallenge::new("domain", "address", "new", 0, u64::MAX);
+    let session = Session {
+        token: "token".to_string(),
+        address: "address".to_string(),
+        expires_at: 1,
+    };
+
+    for (key, value) in [
+        (
+            "challenge:address:old",
+            bincode::serialize(&expired).unwrap(),
+        ),
+        (
+            "challenge:address:new",
+            bincode::serialize(&pending).unwrap(),
+        ),
+        ("session:token", bincode::serialize(&session).unwrap()),
+        ("session:corrupt", b"not bincode".to_vec()),
+    ] {
+        db.create(Keyspace::Sessions, key, &value).await.unwrap();
+    }
+
+    purge_expired_sessions(&db).await.unwrap();
+
+    let remaining = db
+        .entries(Keyspace::Sessions)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|(key, _)| key)
+        .collect::<Vec<String>>();
+    assert_eq!(remaining, vec!["challenge:address:new"]);
+}
+
+fn signed_headers(
+    signing_key: &SigningKey,
+    method: &str,
+    path: &str,
+    body: &[u8],
+) -> Vec<Header<'static>> {
+    let timestamp = unix_timestamp();
+    let body_hash = blake3::hash(body);
+    let signature = signing_key.sign(&SignedHeaders::message(method, path, &body_hash, timestamp));
+
+    vec![
+        Header::new(
+            SignedHeaders::SIGNER,
+            Publisher::public_key_to_base58(&signing_key.verifying_key()),
+        ),
+        Header::new(SignedHeaders::TIMESTAMP, timestamp.to_string()),
+        Header::new(SignedHeaders::BODY_HASH, body_hash.to_hex().to_string()),
+        Header::new(
+            SignedHeaders::SIGNATURE,
+            bs58::encode(signature.to_bytes()).into_string(),
+        ),
+    ]
+}
+
+#[rocket::async_test]
+async fn authenticates_publishers_from_signed_headers() {
+    let (client, _) = client().await;
+    let owner = signing_key(1);
+    let publisher = register(&client, &owner, 1).await;
+
+    let mut request = client.post("/publisher/me");
+    for header in signed_headers(&owner, "POST", "/publisher/me", &[]) {
+        request = request.header(header);
+    }
+    let response = request.dispatch().await;
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(json::<Publisher>(response).await, publisher);
+
+    let response = client.post("/publisher/me").dispatch().await;
+    assert_error(response, Status::Unauthorized, ErrorCode::Unauthorized).await;
+
+    let mut request = client.post("/publisher/me");
+    for header in signed_headers(&signing_key(9), "POST", "/publisher/me", &[]) {
+        request = request.header(header);
+    }
+    let response = request.dispatch().await;
+    assert_error(response, Status::Forbidden, ErrorCode::PermissionDenied).await;
+
+    let mut request = client.post("/publisher/me");
+    for header in signed_headers(&owner, "POST", "/somewhere-else", &[]) {
+        request = request.header(header);
+    }
+    let response = request.dispatch().await;
+    assert_error(response, Status::Unauthorized, ErrorCode::InvalidSignature).await;
+
+    // The guard never reads a body, so headers signed over one do not pass
+    let mut request = client.post("/publisher/me");
+    for header in signed_headers(&owner, "POST", "/publisher/me", b"{}") {
+        request = request.header(header);
+    }
+    let response = request.dispatch().await;
+    assert_error(response, Status::Unauthorized, ErrorCode::InvalidSignature).await;
+}
+
+#[rocket::async_test]
+async fn onboards_a_publisher_with_its_first_project() {
+    let (client, _) = client().await;
+    let owner = signing_key(1);
+    let publisher = publisher_for(&owner);
+    let project = project_for(&publisher, "Bar Project");
+    let onboarding = Onboarding {
+        publisher: publisher.clone(),
+        project: project.clone(),
+    };
+
+    let response = post_json(&client, "/publisher-onboard", signed(onboarding, &owner, 1)).await;
+    assert_eq!(response.status(), Status::Ok);
+
+    let response = client
+        .post(format!("/publisher-info/{}", publisher.address()))
+        .dispatch()
+        .await;
+    assert_eq!(json::<Publisher>(response).await, publisher);
+
+    let response = client
+        .post(format!("/project-info/{}", project.id_base58()))
+        .dispatch()
+        .await;
+    assert_eq!(json::<Project>(response).await, project);
+}
+
+#[rocket::async_test]
+async fn failed_onboarding_stores_nothing() {
+    let (client, _) = client().await;
+    let newcomer = signing_key(2);
+    let publisher = publisher_for(&newcomer);
+    let onboarding = Onboarding {
+        project: project_for(&publisher, "***"),
+        publisher: publisher.clone(),
+    };
+    let body = signed(onboarding, &newcomer, 1);
+
+    let response = post_json(&client, "/publisher-onboard", body.clone()).await;
+    assert_error(response, Status::BadRequest, ErrorCode::BadRequest).await;
+
+    let response = client
+        .post(format!("/publisher-info/{}", publisher.address()))
+        .dispatch()
+        .await;
+    assert_error(response, Status::NotFound, ErrorCode::NotFound).await;
+
+    let response = client.get("/search?q=foo%20org").dispatch().await;
+    assert!(json::<SearchResults>(response).await.publishers.is_empty());
+
+    // The nonce is only consumed when the request succeeds
+    let response = post_json(&client, "/publisher-onboard", body).await;
+    assert_error(response, Status::BadRequest, ErrorCode::BadRequest).await;
+}
+
+#[rocket::async_test]
+async fn reads_projects_by_hex_id_and_alias() {
+    let (client, _) = client().await;
+    let owner = signing_key(1);
+    let publisher = register(&client, &owner, 1).await;
+    let project = project_for(&publisher, "Bar Project");
+    create(&client, &project, &owner, 2).await;
+
+    for uri in [
+        format!("/project-info/{}", project.id_hex()),
+        format!("/project-info/{}/bar-project", publisher.address()),
+        format!(
+            "/project-info/{}/{}",
+            publisher.address(),
+            path_segment(&project.name)
+        ),
+    ] {
+        let response = client.post(uri).dispatch().await;
+        assert_eq!(json::<Project>(response).await, project);
+    }
+
+    let response = client
+        .post(format!("/project-info/{}/missing", publisher.address()))
+        .dispatch()
+        .await;
+    assert_error(response, Status::NotFound, ErrorCode::NotFound).await;
+}
+
+#[rocket::async_test]
+async fn derives_project_ids_on_create() {
+    let (client, _) = client().await;
+    let owner = signing_key(1);
+    let publisher = register(&client, &owner, 1).await;
+    let expected = project_for(&publisher, "Bar Project");
+    let project = Project {
+        id: blake3::hash(b"chosen by the client"),
+        ..expected.clone()
+    };
+
+    let response = post_json(&client, "/project-create", signed(project, &owner, 2)).await;
+    assert_eq!(json::<String>(response).await, expected.id_base58());
+}
+
+#[rocket::async_test]
+async fn publishers_can_share_project_names() {
+    let (client, _) = client().await;
+    let first = signing_key(1);
+    let second = signing_key(2);
+    let first_project = project_for(&register(&client, &first, 1).await, "Bar Project");
+    let second_project = project_for(&register(&client, &second, 1).await, "Bar Project");
+
+    create(&client, &first_project, &first, 2).await;
+    create(&client, &second_project, &second, 2).await;
+    assert_ne!(first_project.id, second_project.id);
+
+    let response = client.get("/projects").dispatch().await;
+    assert_eq!(json::<ProjectPage>(response).await.projects.len(), 2);
+}
+
+#[rocket::async_test]
+async fn renaming_keeps_the_id_and_moves_the_alias() {
+    let (client, _) = client().await;
+    let owner = signing_key(1);
+    let publisher = register(&client, &owner, 1).await;
+    let original = project_for(&publisher, "Bar Project");
+    let other = project_for(&publisher, "Other");
+    create(&client, &original, &owner, 2).await;
+    create(&client, &other, &owner, 3).await;
+
+    let renamed = Project {
+        name: "Baz Project".to_string(),
+        ..original.clone()
+    };
+    let response = post_json(
+        &client,
+        "/project-update",
+        signed(renamed.clone(), &owner, 4),
+    )
+    .await;
+    assert_eq!(json::<String>(response).await, original.id_base58());
+
+    let response = client
+        .post(format!("/project-info/{}", original.id_base58()))
+        .dispatch()
+        .await;
+    assert_eq!(json::<Project>(response).await, renamed);
+
+    let response = client
+        .post(format!("/project-info/{}/baz-project", publisher.address()))
+        .dispatch()
+        .await;
+    assert_eq!(json::<Project>(response).await, renamed);
+
+    let response = client
+        .post(format!("/project-info/{}/bar-project", publisher.address()))
+        .dispatch()
+        .await;
+    assert_error(response, Status::NotFound, ErrorCode::NotFound).await;
+
+    let taken = Project {
+        name: "other".to_string(),
+        ..renamed
+    };
+    let response = post_json(&client, "/project-update", signed(taken, &owner, 5)).await;
+    assert_error(response, Status::Conflict, ErrorCode::AlreadyExists).await;
+}
+
+async fn history(client: &Client, publisher: &Publisher) -> Vec<PublisherRevision> {
+    let response = client
+        .get(format!("/publisher-info/{}/history", publisher.address()))
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+
+    json(response).await
+}
+
+#[rocket::async_test]
+async fn updates_a_publisher_and_keeps_its_history() {
+    let (client, _) = client().await;
+    let owner = signing_key(1);
+    let publisher = register(&client, &owner, 1).await;
+
+    let updated = Publisher {
+        name: "Foo Labs".to_string(),
+        website: "https://foo.example".to_string(),
+        mint: signing_key(9).verifying_key(),
+        ..publisher.clone()
+    };
+    let edited_at = unix_timestamp();
+    let response = post_json(
+        &client,
+        "/publisher-update",
+        signed_at(updated.clone(), &owner, 2, edited_at),
+    )
+    .await;
+    assert_eq!(json::<String>(response).await, publisher.address());
+
+    let response = client
+        .post(format!("/publisher-info/{}", publisher.address()))
+        .dispatch()
+        .await;
+    assert_eq!(json::<Publisher>(response).await, updated);
+
+    let revisions = history(&client, &publisher).await;
+    assert_eq!(revisions.len(), 2);
+    assert_eq!(revisions[0].publisher, publisher);
+    assert_eq!(revisions[1].publisher, updated);
+    assert_eq!(revisions[1].edited_at, Some(edited_at));
+    assert_eq!(revisions[1].signer, owner.verifying_key());
+    assert_eq!(
+        updated.changed_fields(&publisher),
+        vec!["name", "website", "mint"]
+    );
+}
+
+#[rocket::async_test]
+async fn starts_the_history_of_publishers_registered_before_it() {
+    let (client, db) = client().await;
+    let owner = signing_key(1);
+    let publisher = publisher_for(&owner);
+    db.create(
+        Keyspace::Publishers,
+        &publisher.address(),
+        &encode(&publisher).unwrap(),
+    )
+    .await
+    .unwrap();
+    assert!(history(&client, &publisher).await.is_empty());
+
+    let updated = Publisher {
+        name: "Foo Labs".to_string(),
+        ..publisher.clone()
+    };
+    let response = post_json(
+        &client,
+        "/publisher-update",
+        signed(updated.clone(), &owner, 1),
+    )
+    .await;
+    assert_eq!(response.status(), Status::Ok);
+
+    let revisions = history(&client, &publisher).await;
+    assert_eq!(revisions.len(), 2);
+    assert_eq!(revisions[0].publisher, publisher);
+    assert_eq!(revisions[0].edited_at, None);
+    assert_eq!(revisions[1].publisher, updated);
+}
+
+#[rocket::async_test]
+async fn only_the_owner_can_update_a_publisher() {
+    let (client, _) = client().await;
+    let owner = signing_key(1);
+    let intruder = signing_key(2);
+    let publisher = register(&client, &owner, 1).await;
+    register(&client, &intruder, 1).await;
+
+    let updated = Publisher {
+        name: "Stolen".to_string(),
+        ..publisher.clone()
+    };
+    let response = post_json(&client, "/publisher-update", signed(updated, &intruder, 2)).await;
+    assert_error(response, Status::Forbidden, ErrorCode::PermissionDenied).await;
+
+    assert_eq!(history(&client, &publisher).await.len(), 1);
+}
+
+#[rocket::async_test]
+async fn history_of_a_missing_publisher_is_not_found() {
+    let (client, _) = client().await;
+
+    let response = client
+        .get(format!(
+            "/publisher-info/{}/history",
+            publisher_for(&signing_key(1)).address()
+        ))
+        .dispatch()
+        .await;
+    assert_error(response, Status::NotFound, ErrorCode::NotFound).await;
+}
+
+#[rocket::async_test]
+async fn renaming_a_publisher_renames_it_on_its_projects() {
+    let (client, _) = client().await;
+    let owner = signing_key(1);
+    let publisher = register(&client, &owner, 1).await;
+    let project = project_for(&publisher, "Bar Project");
+    create(&client, &project, &owner, 2).await;
+
+    let updated = Publisher {
+        name: "Foo Labs".to_string(),
+        ..publisher
+    };
+    let response = post_json(&client, "/publisher-update", signed(updated, &owner, 3)).await;
+    assert_eq!(response.status(), Status::Ok);
+
+    let response = client
+        .post(format!("/project-info/{}", project.id_base58()))
+        .dispatch()
+        .await;
+    assert_eq!(json::<Project>(response).await.publisher.2, "Foo Labs");
+}
+
+/// Registers the publisher of `owner` with `signers` besides its own key
+async fn register_with(
+    client: &Client,
+    owner: &SigningKey,
+    signers: Vec<PublisherSigner>,
+    nonce: u8,
+) -> Publisher {
+    let publisher = Publisher {
+        signers,
+        ..publisher_for(owner)
+    };
+    let response = post_json(
+        client,
+        "/publishers",
+        signed(publisher.clone(), owner, nonce),
+    )
+    .await;
+    assert_eq!(response.status(), Status::Ok);
+
+    publisher
+}
+
+fn signer(signing_key: &SigningKey, role: SignerRole) -> PublisherSigner {
+    PublisherSigner {
+        public_key: signing_key.verifying_key(),
+        role,
+    }
+}
+
+#[rocket::async_test]
+async fn editors_maintain_the_profile_and_projects() {
+    let (client, _) = client().await;
+    let owner = signing_key(1);
+    let editor = signing_key(2);
+    let publisher = register_with(
+        &client,
+        &owner,
+        vec![signer(&editor, SignerRole::Editor)],
+        1,
+    )
+    .await;
+
+    let renamed = Publisher {
+        name: "Foo Labs".to_string(),
+        ..publisher.clone()
+    };
+    let response = post_json(
+        &client,
+        "/publisher-update",
+        signed(renamed.clone(), &editor, 1),
+    )
+    .await;
+    assert_eq!(response.status(), Status::Ok);
+
+    let project = project_for(&renamed, "Bar Project");
+    create(&client, &project, &editor, 2).await;
+
+    let mut request = client.post("/publisher/me");
+    for header in signed_headers(&editor, "POST", "/publisher/me", &[]) {
+        request = request.header(header);
+    }
+    let response = request.dispatch().await;
+    assert_eq!(json::<Publisher>(response).await, renamed);
+
+    let payout = Publisher {
+        mint: editor.verifying_key(),
+        ..renamed.clone()
+    };
+    let response = post_json(&client, "/publisher-update", signed(payout, &editor, 3)).await;
+    assert_error(response, Status::Forbidden, ErrorCode::PermissionDenied).await;
+
+    let promoted = Publisher {
+        signers: vec![signer(&editor, SignerRole::Owner)],
+        ..renamed
+    };
+    let response = post_json(&client, "/publisher-update", signed(promoted, &editor, 4)).await;
+    assert_error(response, Status::Forbidden, ErrorCode::PermissionDenied).await;
+}
+
+#[rocket::async_test]
+async fn rejects_duplicate_signers() {
+    let (client, _) = client().await;
+    let owner = signing_key(1);
+    let publisher = Publisher {
+        signers: vec![signer(&owner, SignerRole::Editor)],
+        ..publisher_for(&owner)
+    };
+
+    let response = post_json(&client, "/publishers", signed(publisher, &owner, 1)).await;
+    assert_error(response, Status::BadRequest, ErrorCode::BadRequest).await;
+}
+
+#[rocket::async_test]
+async fn rejects_invalid_fields_on_writes() {
+    let (client, _) = client().await;
+    let owner = signing_key(1);
+    let publisher = Publisher {
+        name: " ".to_string(),
+        website: "http://example.org".to_string(),
+        ..publisher_for(&owner)
+    };
+
+    let response = post_json(&client, "/publishers", signed(publisher, &owner, 1)).await;
+    assert_eq!(response.status(), Status::BadRequest);
+    let error = json::<ApiError>(response).await;
+    assert_eq!(error.code, ErrorCode::BadRequest);
+    assert_eq!(
+        error
+            .fields
+            .iter()
+            .map(|field| field.field.as_str())
+            .collect::<Vec<&str>>(),
+        vec!["name", "website"]
+    );
+
+    let publisher = register(&client, &owner, 2).await;
+    let project = project_for(&publisher, "foo");
+    create(&client, &project, &owner, 3).await;
+
+    let invalid = Project {
+        docs: "javascript:alert(1)".to_string(),
+        ..project.clone()
+    };
+    let response = post_json(&client, "/project-update", signed(invalid, &owner, 4)).await;
+    assert_eq!(response.status(), Status::BadRequest);
+    let error = json::<ApiError>(response).await;
+    assert_eq!(
+        FieldError::message_for(&error.fields, "docs"),
+        Some("Must be an https:// link".to_string())
+    );
+}
+
+#[rocket::async_test]
+async fn rotates_a_publisher_to_a_new_key() {
+    let (client, db) = client().await;
+    let old_key = signing_key(1);
+    let new_key = signing_key(2);
+    let publisher = register_with(
+        &client,
+        &old_key,
+        vec![signer(&new_key, SignerRole::Owner)],
+        1,
+    )
+    .await;
+    let project = project_for(&publisher, "Bar Project");
+    create(&client, &project, &old_key, 2).await;
+
+    let rotation = KeyRotation {
+        address: publisher.address(),
+        new_key: new_key.verifying_key(),
+    };
+    let response = post_json(&client, "/publisher-rotate", signed(rotation, &old_key, 3)).await;
+    let address = json::<String>(response).await;
+    assert_eq!(
+        address,
+        Publisher::public_key_to_base58(&new_key.verifying_key())
+    );
+
+    let rotated = Publisher {
+        public_key: new_key.verifying_key(),
+        signers: Vec::default(),
+        ..publisher.clone()
+    };
+    let response = client
+        .post(format!("/publisher-info/{address}"))
+        .dispatch()
+        .await;
+    assert_eq!(json::<Publisher>(response).await, rotated);
+
+    let response = client
+        .post(format!("/publisher-info/{}", publisher.address()))
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::PermanentRedirect);
+    assert_eq!(
+        response.headers().get_one("Location"),
+        Some(format!("/publisher-info/{address}").as_str())
+    );
+
+    let response = client
+        .post(format!("/project-info/{}/bar-project", publisher.address()))
+        .dispatch()
+        .await;
+    assert_eq!(
+        response.headers().get_one("Location"),
+        Some(format!("/project-info/{address}/bar-project").as_str())
+    );
+
+    let response = client
+        .post(format!("/project-info/{}", project.id_base58()))
+        .dispatch()
+        .await;
+    let moved = json::<Project>(response).await;
+    assert_eq!(moved.publisher_address(), address);
+    assert_eq!(moved.alias(), address.clone() + "/bar-project");
+
+    let revisions = history(&client, &rotated).await;
+    assert_eq!(revisions.len(), 2);
+    assert_eq!(revisions[0].publisher, publisher);
+    assert_eq!(revisions[1].publisher, rotated);
+
+    // The history stays where it was first recorded and is never rewritten
+    let history_keys = db
+        .entries(Keyspace::PublisherHistory)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|(key, _)| key)
+        .collect::<Vec<String>>();
+    assert!(history_keys
+        .iter()
+        .all(|key| key.starts_with(&publisher.address())));
+
+    let response = post_json(
+        &client,
+        "/publisher-update",
+        signed(publisher.clone(), &old_key, 4),
+    )
+    .await;
+    assert_error(response, Status::Forbidden, ErrorCode::PermissionDenied).await;
+
+    let response = post_json(&client, "/publishers", signed(publisher, &old_key, 5)).await;
+    assert_error(response, Status::Conflict, ErrorCode::AlreadyExists).await;
+}
+
+#[rocket::async_test]
+async fn numbers_revisions_from_a_stored_counter() {
+    let (client, db) = client().await;
+    let owner = signing_key(1);
+    let publisher = register(&client, &owner, 1).await;
+
+    let mut txn = db.begin().await.unwrap();
+    record_revision(&mut txn, &publisher, &owner.verifying_key(), Some(1)).unwrap();
+    txn.commit().unwrap();
+
+    // A revision going missing must not make the next one reuse a number
+    let first = [publisher.address().as_bytes(), &[0], &0u64.to_be_bytes()].concat();
+    let mut txn = db.begin().await.unwrap();
+    txn.delete(Keyspace::PublisherHistory, &first).unwrap();
+    record_revision(&mut txn, &publisher, &owner.verifying_key(), Some(2)).unwrap();
+    txn.commit().unwrap();
+
+    let revisions = history(&client, &publisher).await;
+    assert_eq!(
+        revisions
+            .iter()
+            .map(|revision| revision.edited_at)
+            .collect::<Vec<Option<u64>>>(),
+        vec![Some(1), Some(2)]
+    );
+}
+
+#[rocket::async_test]
+async fn inserts_records_like_the_api() {
+    let (client, db) = client().await;
+    let owner = signing_key(1);
+    let publisher = publisher_for(&owner);
+
+    let mut txn = db.begin().await.unwrap();
+    insert_publisher(&mut txn, &publisher, 1).unwrap();
+
+    // Imported projects are given the id derived from their publisher and name
+    let project = project_for(&publisher, "Bar Project");
+    let imported = Project {
+        id: blake3::hash(b"arbitrary"),
+        ..project.clone()
+    };
+    let key = insert_project(&mut txn, imported.clone(), publisher.clone()).unwrap();
+    assert_eq!(key, project.id_base58());
+
+    assert_eq!(
+        insert_project(&mut txn, imported, publisher.clone()),
+        Err(BackendError::KvAlreadyExists)
+    );
+    let invalid = Project {
+        docs: "javascript:alert(1)".to_string(),
+        ..project_for(&publisher, "Baz Project")
+    };
+    assert!(matches!(
+        insert_project(&mut txn, invalid, publisher.clone()),
+        Err(BackendError::InvalidFields(_))
+    ));
+    txn.commit().unwrap();
+
+    let revisions = history(&client, &publisher).await;
+    assert_eq!(revisions.len(), 1);
+
+    let response = client.post(format!("/project-info/{key}")).dispatch().await;
+    assert_eq!(json::<Project>(response).await, project);
+}
+
+#[rocket::async_test]
+async fn moves_a_misfiled_publisher_to_its_address() {
+    let (client, db) = client().await;
+    let owner = signing_key(1);
+    let publisher = publisher_for(&owner);
+    db.create_indexed(Keyspace::Publishers, "misfiled", &publisher)
+        .await
+        .unwrap();
+
+    let mut txn = db.begin().await.unwrap();
+    let address = move_publisher(&mut txn, "misfiled", &publisher).unwrap();
+    txn.commit().unwrap();
+    assert_eq!(address, publisher.address());
+
+    let response = client
+        .post(format!("/publisher-info/{address}"))
+        .dispatch()
+        .await;
+    assert_eq!(json::<Publisher>(response).await, publisher);
+
+    let response = client.post("/publisher-info/misfiled").dispatch().await;
+    assert_eq!(
+        response.headers().get_one("Location"),
+        Some(format!("/publisher-info/{address}").as_str())
+    );
+
+    let response = client.get("/search?q=foo").dispatch().await;
+    assert_eq!(
+        json::<SearchResults>(response).await.publishers,
+        vec![publisher]
+    );
+}
+
+#[rocket::async_test]
+async fn co_owners_cannot_evict_the_primary_key() {
+    let (client, _) = client().await;
+    let primary = signing_key(1);
+    let co_owner = signing_key(2);
+    let publisher = register_with(
+        &client,
+        &primary,
+        vec![signer(&co_owner, SignerRole::Owner)],
+        1,
+    )
+    .await;
+
+    // The approval of one of two owners is not enough to move the publisher
+    let rotation = KeyRotation {
+        address: publisher.address(),
+        new_key: co_owner.verifying_key(),
+    };
+    let response = post_json(&client, "/publisher-rotate", signed(rotation, &co_owner, 2)).await;
+    assert_eq!(json::<String>(response).await, publisher.address());
+
+    let response = client
+        .post(format!("/publisher-info/{}", publisher.address()))
+        .dispatch()
+        .await;
+    assert_eq!(json::<Publisher>(response).await, publisher);
+}
+
+#[rocket::async_test]
+async fn co_owners_together_replace_a_lost_key() {
+    let (client, db) = client().await;
+    let lost = signing_key(1);
+    let first = signing_key(2);
+    let second = signing_key(3);
+    let publisher = register_with(
+        &client,
+        &lost,
+        vec![
+            signer(&first, SignerRole::Owner),
+            signer(&second, SignerRole::Owner),
+        ],
+        1,
+    )
+    .await;
+
+    let rotation = KeyRotation {
+        address: publisher.address(),
+        new_key: first.verifying_key(),
+    };
+    let response = post_json(
+        &client,
+        "/publisher-rotate",
+        signed(rotation.clone(), &first, 1),
+    )
+    .await;
+    assert_eq!(json::<String>(response).await, publisher.address());
+
+    // Two of the three owners make a majority
+    let response = post_json(&client, "/publisher-rotate", signed(rotation, &second, 1)).await;
+    let address = json::<String>(response).await;
+    assert_eq!(
+        address,
+        Publisher::public_key_to_base58(&first.verifying_key())
+    );
+
+    let response = client
+        .post(format!("/publisher-info/{address}"))
+        .dispatch()
+        .await;
+    assert_eq!(
+        json::<Publisher>(response).await,
+        Publisher {
+            public_key: first.verifying_key(),
+            signers: vec![signer(&second, SignerRole::Owner)],
+            ..publisher
+        }
+    );
+    assert!(db
+        .entries(Keyspace::RotationApprovals)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[rocket::async_test]
+async fn only_owners_can_rotate_to_their_own_key() {
+    let (client, _) = client().await;
+    let owner = signing_key(1);
+    let editor = signing_key(2);
+    let publisher = register_with(
+        &client,
+        &owner,
+        vec![signer(&editor, SignerRole::Editor)],
+        1,
+    )
+    .await;
+
+    let to_editor = KeyRotation {
+        address: publisher.address(),
+        new_key: editor.verifying_key(),
+    };
+    let response = post_json(
+        &client,
+        "/publisher-rotate",
+        signed(to_editor.clone(), &editor, 1),
+    )
+    .await;
+    assert_error(response, Status::Forbidden, ErrorCode::PermissionDenied).await;
+
+    let response = post_json(&client, "/publisher-rotate", signed(to_editor, &owner, 2)).await;
+    assert_error(response, Status::BadRequest, ErrorCode::BadRequest).await;
+
+    let response = client
+        .post(format!("/publisher-info/{}", publisher.address()))
+        .dispatch()
+        .await;
+    assert_eq!(json::<Publisher>(response).await, publisher);
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use surrealkv::Store;
+use trenchesfund_backend::{DatabaseConfig, DbState, Keyspace};
+
+use common::scratch_config;
+
+/// Writes `entries` to a per-dataset store the way earlier releases did
+fn write_legacy_store(config: &DatabaseConfig, store_name: &str, entries: &[(&str, &str)]) {
+    let store = Store::new(config.store_options(store_name)).unwrap();
+
+    let mut txn = store.begin().unwrap();
+    for (key, value) in entries {
+        txn.set(key.as_bytes(), value.as_bytes()).unwrap();
+    }
+    txn.commit().unwrap();
+
+    store.close().unwrap();
+}
+
+#[rocket::async_test]
+async fn imports_legacy_stores_once() {
+    let config = scratch_config("legacy-import");
+
+    // Entries already in the single store win over the legacy ones
+    let db = DbState::open(&config).await.unwrap();
+    db.create(Keyspace::Publishers, "kept", b"current")
+        .await
+        .unwrap();
+    drop(db);
+
+    write_legacy_store(
+        &config,
+        &config.stores.publishers,
+        &[("kept", "legacy"), ("added", "legacy")],
+    );
+    write_legacy_store(&config, &config.stores.projects, &[("project", "legacy")]);
+
+    let db = DbState::open(&config).await.unwrap();
+
+    assert_eq!(
+        db.read(Keyspace::Publishers, "kept").await.unwrap(),
+        b"current"
+    );
+    assert_eq!(
+        db.read(Keyspace::Publishers, "added").await.unwrap(),
+        b"legacy"
+    );
+    assert_eq!(
+        db.read(Keyspace::Projects, "project").await.unwrap(),
+        b"legacy"
+    );
+
+    let legacy_dir = config.store_dir(&config.stores.publishers);
+    assert!(!legacy_dir.exists());
+    assert!(legacy_dir.with_extension("imported").is_dir());
+    assert!(config
+        .store_dir(&config.stores.projects)
+        .with_extension("imported")
+        .is_dir());
+
+    // A second start finds nothing left to import
+    db.purge(Keyspace::Publishers, "added").await.unwrap();
+    drop(db);
+
+    let db = DbState::open(&config).await.unwrap();
+    assert_eq!(db.entries(Keyspace::Publishers).await.unwrap().len(), 1);
+    drop(db);
+
+    let _ = std::fs::remove_dir_all(&config.dir);
+}
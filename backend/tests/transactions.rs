@@ -0,0 +1,151 @@
+mod common;
+
+use std::{ops::Bound, path::PathBuf};
+
+use trenchesfund_backend::{BackendError, DbState, Keyspace, RECORDS_END};
+
+use common::scratch_config;
+
+/// The in-memory store and a surrealkv store on disk, so that every case
+/// checks both implementations of the storage contract
+async fn backends(name: &str) -> Vec<(DbState, Option<PathBuf>)> {
+    let config = scratch_config(name);
+
+    vec![
+        (DbState::in_memory(), None),
+        (DbState::open(&config).await.unwrap(), Some(config.dir)),
+    ]
+}
+
+fn cleanup(dir: Option<PathBuf>) {
+    if let Some(dir) = dir {
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
+
+#[rocket::async_test]
+async fn concurrent_creates_of_one_key_conflict() {
+    for (db, dir) in backends("concurrent_creates_of_one_key_conflict").await {
+        let mut first = db.begin().await.unwrap();
+        let mut second = db.begin().await.unwrap();
+
+        first.create(Keyspace::Publishers, "key", b"first").unwrap();
+        second
+            .create(Keyspace::Publishers, "key", b"second")
+            .unwrap();
+
+        first.commit().unwrap();
+        assert_eq!(second.commit(), Err(BackendError::TransactionConflict));
+
+        assert_eq!(
+            db.read(Keyspace::Publishers, "key").await.unwrap(),
+            b"first"
+        );
+
+        drop(db);
+        cleanup(dir);
+    }
+}
+
+#[rocket::async_test]
+async fn transactions_on_other_keys_do_not_conflict() {
+    for (db, dir) in backends("transactions_on_other_keys_do_not_conflict").await {
+        let mut first = db.begin().await.unwrap();
+        let mut second = db.begin().await.unwrap();
+
+        first
+            .create(Keyspace::Publishers, "first", b"first")
+            .unwrap();
+        second
+            .create(Keyspace::Publishers, "second", b"second")
+            .unwrap();
+
+        first.commit().unwrap();
+        second.commit().unwrap();
+
+        drop(db);
+        cleanup(dir);
+    }
+}
+
+#[rocket::async_test]
+async fn writes_across_keyspaces_commit_together() {
+    for (db, dir) in backends("writes_across_keyspaces_commit_together").await {
+        let mut txn = db.begin().await.unwrap();
+        txn.create(Keyspace::Publishers, "key", b"publisher")
+            .unwrap();
+        txn.create(Keyspace::Projects, "key", b"project").unwrap();
+        drop(txn);
+
+        assert_eq!(
+            db.read(Keyspace::Publishers, "key").await,
+            Err(BackendError::KvKeyNotFound)
+        );
+
+        let mut txn = db.begin().await.unwrap();
+        txn.create(Keyspace::Publishers, "key", b"publisher")
+            .unwrap();
+        txn.create(Keyspace::Projects, "key", b"project").unwrap();
+        txn.commit().unwrap();
+
+        assert_eq!(
+            db.read(Keyspace::Publishers, "key").await.unwrap(),
+            b"publisher"
+        );
+        assert_eq!(
+            db.read(Keyspace::Projects, "key").await.unwrap(),
+            b"project"
+        );
+        assert_eq!(db.entries(Keyspace::Projects).await.unwrap().len(), 1);
+
+        drop(db);
+        cleanup(dir);
+    }
+}
+
+#[rocket::async_test]
+async fn scans_honour_range_bounds() {
+    for (db, dir) in backends("scans_honour_range_bounds").await {
+        for key in ["a", "b", "c", "d"] {
+            db.create(Keyspace::Projects, key, b"project")
+                .await
+                .unwrap();
+        }
+        db.create(Keyspace::Publishers, "a", b"publisher")
+            .await
+            .unwrap();
+
+        let mut txn = db.begin_read().await.unwrap();
+        let mut keys = |range, limit| {
+            txn.scan(Keyspace::Projects, range, limit)
+                .unwrap()
+                .into_iter()
+                .map(|(key, _)| String::from_utf8(key).unwrap())
+                .collect::<Vec<String>>()
+        };
+
+        assert_eq!(keys((Bound::Unbounded, RECORDS_END), Some(2)), ["a", "b"]);
+        assert_eq!(
+            keys((Bound::Excluded(b"b".as_slice()), RECORDS_END), None),
+            ["c", "d"]
+        );
+        assert_eq!(
+            keys((Bound::Included(b"b".as_slice()), RECORDS_END), Some(2)),
+            ["b", "c"]
+        );
+        assert_eq!(
+            keys(
+                (
+                    Bound::Excluded(b"a".as_slice()),
+                    Bound::Included(b"c".as_slice())
+                ),
+                None
+            ),
+            ["b", "c"]
+        );
+        drop(txn);
+
+        drop(db);
+        cleanup(dir);
+    }
+}
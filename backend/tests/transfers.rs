@@ -0,0 +1,491 @@
+mod common;
+
+use std::collections::{HashMap, HashSet};
+
+use ed25519_dalek::SigningKey;
+use rocket::{
+    http::{ContentType, Status},
+    local::asynchronous::Client,
+};
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+use trenchesfund_backend::{
+    confirm_transfers, DbState, Keyspace, SolanaRpc, MAX_PENDING_TRANSFERS,
+};
+use trenchesfund_common::{
+    ApiError, DonationRequest, ErrorCode, FieldError, Publisher, TokenTotal, TransferRequest,
+    TransferStatus, NATIVE_MINT,
+};
+
+use common::client;
+
+const VALIDITY_SECS: u64 = 3600;
+
+/// What the mock RPC answers for each method, keyed by the first parameter
+#[derive(Default, Clone)]
+struct Ledger {
+    /// `getSignaturesForAddress` results by address
+    signatures: HashMap<String, Value>,
+    /// `getTransaction` results by signature
+    transactions: HashMap<String, Value>,
+    /// Keys whose calls are answered with an error
+    failing: HashSet<String>,
+}
+
+async fn answer(mut stream: TcpStream, ledger: Ledger) {
+    let mut request = Vec::<u8>::new();
+    let mut buffer = [0u8; 4096];
+
+    // Read the headers, then the body announced by `content-length`
+    let body_start = loop {
+        let read = stream.read(&mut buffer).await.unwrap();
+        request.extend_from_slice(&buffer[..read]);
+
+        if let Some(end) = request.windows(4).position(|window| window == b"\r\n\r\n") {
+            break end + 4;
+        }
+    };
+    let headers = String::from_utf8_lossy(&request[..body_start]).to_lowercase();
+    let length = headers
+        .lines()
+        .find_map(|line| line.strip_prefix("content-length:"))
+        .and_then(|length| length.trim().parse::<usize>().ok())
+        .unwrap_or_default();
+
+    while request.len() < body_start + length {
+        let read = stream.read(&mut buffer).await.unwrap();
+        request.extend_from_slice(&buffer[..read]);
+    }
+
+    let call = serde_json::from_slice::<Value>(&request[body_start..]).unwrap();
+    let key = call["params"][0].as_str().unwrap_or_default();
+    let result = match call["method"].as_str() {
+        Some("getSignaturesForAddress") => ledger.signatures.get(key).cloned().unwrap_or(json!([])),
+        Some("getTransaction") => ledger.transactions.get(key).cloned().unwrap_or(Value::Null),
+        _ => Value::Null,
+    };
+
+    let body = if ledger.failing.contains(key) {
+        json!({ "jsonrpc": "2.0", "id": call["id"], "error": { "code": -32000, "message": "Unavailable" } })
+    } else {
+        json!({ "jsonrpc": "2.0", "id": call["id"], "result": result })
+    }
+    .to_string();
+    let response = format!(
+        "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await.unwrap();
+}
+
+/// Serves `ledger` as a Solana JSON-RPC endpoint on a local port, returning its URL
+async fn mock_rpc(ledger: Ledger) -> SolanaRpc {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            tokio::spawn(answer(stream, ledger.clone()));
+        }
+    });
+
+    SolanaRpc::new(url)
+}
+
+/// Stores a publisher paid in `mint`
+async fn publisher(db: &DbState, seed: u8, mint: &str) -> Publisher {
+    let publisher = Publisher {
+        public_key: SigningKey::from_bytes(&[seed; 32]).verifying_key(),
+        mint: Publisher::base58_to_public_key(mint).unwrap(),
+        ..Publisher::default()
+    };
+    db.create_indexed(Keyspace::Publishers, &publisher.address(), &publisher)
+        .await
+        .unwrap();
+
+    publisher
+}
+
+fn token_mint() -> String {
+    Publisher::public_key_to_base58(&SigningKey::from_bytes(&[9u8; 32]).verifying_key())
+}
+
+async fn request(client: &Client, publisher: &Publisher, amount: u64) -> TransferRequest {
+    let body = DonationRequest {
+        publisher: publisher.address(),
+        project: None,
+        amount,
+    };
+    let response = client
+        .post("/transfer-request")
+        .header(ContentType::JSON)
+        .body(serde_json::to_string(&body).unwrap())
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+
+    response.into_json().await.unwrap()
+}
+
+async fn status(client: &Client, transfer: &TransferRequest) -> TransferStatus {
+    let response = client
+        .get(format!("/transfer-request/{}", transfer.reference))
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+
+    response
+        .into_json::<TransferRequest>()
+        .await
+        .unwrap()
+        .status
+}
+
+/// A transaction paying `paid` base units of a 6 decimals token to `recipient`
+fn token_transfer(recipient: &str, mint: &str, paid: u64) -> Value {
+    json!({
+        "blockTime": 1_700_000_000u64,
+        "meta": {
+            "err": null,
+            "preTokenBalances": [
+                { "accountIndex": 1, "mint": mint, "owner": "Donor", "uiTokenAmount": { "amount": "9000000", "decimals": 6 } },
+                { "accountIndex": 2, "mint": mint, "owner": recipient, "uiTokenAmount": { "amount": "1000000", "decimals": 6 } }
+            ],
+            "postTokenBalances": [
+                { "accountIndex": 1, "mint": mint, "owner": "Donor", "uiTokenAmount": { "amount": (9_000_000 - paid).to_string(), "decimals": 6 } },
+                { "accountIndex": 2, "mint": mint, "owner": recipient, "uiTokenAmount": { "amount": (1_000_000 + paid).to_string(), "decimals": 6 } }
+            ]
+        },
+        "transaction": {
+            "message": {
+                "accountKeys": [{ "pubkey": "Donor" }, { "pubkey": "DonorTokens" }, { "pubkey": "RecipientTokens" }]
+            }
+        }
+    })
+}
+
+#[rocket::async_test]
+async fn requests_a_transfer_in_the_publisher_mint() {
+    let (client, db) = client().await;
+    let publisher = publisher(&db, 1, &token_mint()).await;
+
+    let transfer = request(&client, &publisher, 1_500_000_000).await;
+    assert_eq!(transfer.recipient, publisher.address());
+    assert_eq!(transfer.mint, token_mint());
+    assert_eq!(transfer.status, TransferStatus::Pending);
+    assert!(transfer.url().starts_with(&format!(
+        "solana:{}?amount=1.5&spl-token={}&reference={}",
+        publisher.address(),
+        token_mint(),
+        transfer.reference
+    )));
+
+    // Every request gets its own reference
+    let other = request(&client, &publisher, 1_500_000_000).await;
+    assert_ne!(other.reference, transfer.reference);
+
+    assert_eq!(status(&client, &transfer).await, TransferStatus::Pending);
+}
+
+#[rocket::async_test]
+async fn rejects_invalid_transfer_requests() {
+    let (client, db) = client().await;
+    let publisher = publisher(&db, 1, NATIVE_MINT).await;
+
+    let body = DonationRequest {
+        publisher: publisher.address(),
+        project: None,
+        amount: 0,
+    };
+    let response = client
+        .post("/transfer-request")
+        .header(ContentType::JSON)
+        .body(serde_json::to_string(&body).unwrap())
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::BadRequest);
+    let error = response.into_json::<ApiError>().await.unwrap();
+    assert!(FieldError::message_for(&error.fields, "amount").is_some());
+
+    let body = DonationRequest {
+        publisher: Publisher::public_key_to_base58(
+            &SigningKey::from_bytes(&[2u8; 32]).verifying_key(),
+        ),
+        project: None,
+        amount: 1,
+    };
+    let response = client
+        .post("/transfer-request")
+        .header(ContentType::JSON)
+        .body(serde_json::to_string(&body).unwrap())
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::NotFound);
+    assert_eq!(
+        response.into_json::<ApiError>().await.unwrap().code,
+        ErrorCode::NotFound
+    );
+}
+
+#[rocket::async_test]
+async fn caps_the_pending_requests_of_a_publisher() {
+    let (client, db) = client().await;
+    let other = publisher(&db, 2, &token_mint()).await;
+    let publisher = publisher(&db, 1, &token_mint()).await;
+
+    let mut transfers = Vec::<TransferRequest>::new();
+    for _ in 0..MAX_PENDING_TRANSFERS {
+        transfers.push(request(&client, &publisher, 1_500_000_000).await);
+    }
+
+    let body = DonationRequest {
+        publisher: publisher.address(),
+        project: None,
+        amount: 1_500_000_000,
+    };
+    let response = client
+        .post("/transfer-request")
+        .header(ContentType::JSON)
+        .body(serde_json::to_string(&body).unwrap())
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::TooManyRequests);
+    assert_eq!(
+        response.into_json::<ApiError>().await.unwrap().code,
+        ErrorCode::TooManyRequests
+    );
+
+    // Other publishers are not affected
+    request(&client, &other, 1_500_000_000).await;
+
+    // A confirmed request no longer counts as pending
+    let mut ledger = Ledger::default();
+    ledger.signatures.insert(
+        transfers[0].reference.clone(),
+        json!([{ "signature": "Paid", "err": null }]),
+    );
+    ledger.transactions.insert(
+        "Paid".to_string(),
+        token_transfer(&publisher.address(), &token_mint(), 1_500_000),
+    );
+    let rpc = mock_rpc(ledger).await;
+    assert_eq!(
+        confirm_transfers(&db, &rpc, transfers[0].requested_at, VALIDITY_SECS).await,
+        Ok(1)
+    );
+
+    request(&client, &publisher, 1_500_000_000).await;
+}
+
+#[rocket::async_test]
+async fn confirms_a_paid_token_transfer() {
+    let (client, db) = client().await;
+    let publisher = publisher(&db, 1, &token_mint()).await;
+    // 1.5 tokens of a 6 decimals mint
+    let transfer = request(&client, &publisher, 1_500_000_000).await;
+
+    let mut ledger = Ledger::default();
+    ledger.signatures.insert(
+        transfer.reference.clone(),
+        json!([{ "signature": "Paid", "err": null }]),
+    );
+    ledger.transactions.insert(
+        "Paid".to_string(),
+        token_transfer(&publisher.address(), &token_mint(), 1_500_000),
+    );
+    let rpc = mock_rpc(ledger).await;
+
+    assert_eq!(
+        confirm_transfers(&db, &rpc, 1_700_000_100, VALIDITY_SECS).await,
+        Ok(1)
+    );
+    assert_eq!(
+        status(&client, &transfer).await,
+        TransferStatus::Confirmed {
+            signature: "Paid".to_string(),
+            donor: "Donor".to_string(),
+            confirmed_at: 1_700_000_000,
+        }
+    );
+
+    // Confirmed requests are not checked again
+    assert_eq!(
+        confirm_transfers(&db, &rpc, 1_700_000_200, VALIDITY_SECS).await,
+        Ok(0)
+    );
+
+    // The payment is recorded once in the donation ledger
+    let totals = || async {
+        client
+            .get(format!(
+                "/donations/totals?publisher={}",
+                publisher.address()
+            ))
+            .dispatch()
+            .await
+            .into_json::<Vec<TokenTotal>>()
+            .await
+            .unwrap()
+    };
+    let expected = vec![TokenTotal {
+        mint: token_mint(),
+        amount: 1_500_000_000,
+        donations: 1,
+    }];
+    assert_eq!(totals().await, expected);
+
+    // The request is dropped once it expired, the donation stays in the ledger
+    assert_eq!(
+        confirm_transfers(&db, &rpc, 1_700_000_000 + VALIDITY_SECS + 1, VALIDITY_SECS).await,
+        Ok(0)
+    );
+    let response = client
+        .get(format!("/transfer-request/{}", transfer.reference))
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::NotFound);
+    assert_eq!(totals().await, expected);
+}
+
+#[rocket::async_test]
+async fn keeps_checking_after_an_rpc_error() {
+    let (client, db) = client().await;
+    let publisher = publisher(&db, 1, &token_mint()).await;
+    let unanswered = request(&client, &publisher, 1_500_000_000).await;
+    let paid = request(&client, &publisher, 1_500_000_000).await;
+
+    let mut ledger = Ledger::default();
+    ledger.failing.insert(unanswered.reference.clone());
+    ledger.signatures.insert(
+        paid.reference.clone(),
+        json!([{ "signature": "Paid", "err": null }]),
+    );
+    ledger.transactions.insert(
+        "Paid".to_string(),
+        token_transfer(&publisher.address(), &token_mint(), 1_500_000),
+    );
+    let rpc = mock_rpc(ledger).await;
+
+    assert_eq!(
+        confirm_transfers(&db, &rpc, paid.requested_at, VALIDITY_SECS).await,
+        Ok(1)
+    );
+    assert_eq!(status(&client, &unanswered).await, TransferStatus::Pending);
+    assert!(matches!(
+        status(&client, &paid).await,
+        TransferStatus::Confirmed { .. }
+    ));
+}
+
+#[rocket::async_test]
+async fn confirms_a_paid_sol_transfer() {
+    let (client, db) = client().await;
+    let publisher = publisher(&db, 1, NATIVE_MINT).await;
+    let transfer = request(&client, &publisher, 250_000_000).await;
+    assert!(!transfer.url().contains("spl-token"));
+
+    let mut ledger = Ledger::default();
+    ledger.signatures.insert(
+        transfer.reference.clone(),
+        json!([{ "signature": "Paid", "err": null }]),
+    );
+    ledger.transactions.insert(
+        "Paid".to_string(),
+        json!({
+            "blockTime": 1_700_000_000u64,
+            "meta": {
+                "err": null,
+                "preBalances": [1_000_000_000u64, 0u64],
+                "postBalances": [749_995_000u64, 250_000_000u64]
+            },
+            "transaction": {
+                "message": {
+                    "accountKeys": [{ "pubkey": "Donor" }, { "pubkey": publisher.address() }]
+                }
+            }
+        }),
+    );
+    let rpc = mock_rpc(ledger).await;
+
+    assert_eq!(
+        confirm_transfers(&db, &rpc, 1_700_000_100, VALIDITY_SECS).await,
+        Ok(1)
+    );
+    assert!(matches!(
+        status(&client, &transfer).await,
+        TransferStatus::Confirmed { .. }
+    ));
+}
+
+#[rocket::async_test]
+async fn ignores_failed_and_short_payments() {
+    let (client, db) = client().await;
+    let publisher = publisher(&db, 1, &token_mint()).await;
+    let transfer = request(&client, &publisher, 1_500_000_000).await;
+
+    let mut failed = token_transfer(&publisher.address(), &token_mint(), 1_500_000);
+    failed["meta"]["err"] = json!({ "InstructionError": [0, "Custom"] });
+
+    let mut ledger = Ledger::default();
+    ledger.signatures.insert(
+        transfer.reference.clone(),
+        json!([
+            { "signature": "Short", "err": null },
+            { "signature": "Failed", "err": null },
+            { "signature": "Rejected", "err": { "InstructionError": [0, "Custom"] } },
+            { "signature": "Unknown", "err": null }
+        ]),
+    );
+    ledger.transactions.insert(
+        "Short".to_string(),
+        token_transfer(&publisher.address(), &token_mint(), 1_499_999),
+    );
+    ledger.transactions.insert("Failed".to_string(), failed);
+    ledger.transactions.insert(
+        "Rejected".to_string(),
+        token_transfer(&publisher.address(), &token_mint(), 1_500_000),
+    );
+    let rpc = mock_rpc(ledger).await;
+
+    assert_eq!(
+        confirm_transfers(&db, &rpc, transfer.requested_at, VALIDITY_SECS).await,
+        Ok(0)
+    );
+    assert_eq!(status(&client, &transfer).await, TransferStatus::Pending);
+}
+
+#[rocket::async_test]
+async fn drops_unpaid_requests_once_expired() {
+    let (client, db) = client().await;
+    let publisher = publisher(&db, 1, &token_mint()).await;
+    let transfer = request(&client, &publisher, 1_500_000_000).await;
+    let rpc = mock_rpc(Ledger::default()).await;
+
+    confirm_transfers(
+        &db,
+        &rpc,
+        transfer.requested_at + VALIDITY_SECS,
+        VALIDITY_SECS,
+    )
+    .await
+    .unwrap();
+    assert_eq!(status(&client, &transfer).await, TransferStatus::Pending);
+
+    confirm_transfers(
+        &db,
+        &rpc,
+        transfer.requested_at + VALIDITY_SECS + 1,
+        VALIDITY_SECS,
+    )
+    .await
+    .unwrap();
+    let response = client
+        .get(format!("/transfer-request/{}", transfer.reference))
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::NotFound);
+}
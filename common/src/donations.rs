@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{TransferRequest, TransferStatus};
+
+/// A confirmed tip, kept in the donation ledger under its transaction signature
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct Donation {
+    pub signature: String,
+    /// The base58 address of the wallet that paid
+    pub donor: String,
+    /// The base58 address of the publisher paid
+    pub publisher: String,
+    /// The base58 id of the project the donation was for, if any
+    pub project: Option<String>,
+    /// The base58 mint of the token paid, see [crate::NATIVE_MINT]
+    pub mint: String,
+    /// In billionths of a token, see [crate::AMOUNT_DECIMALS]
+    pub amount: u64,
+    pub donated_at: u64,
+}
+
+impl Donation {
+    /// The donation paying a confirmed `transfer`, `None` while it is pending
+    pub fn from_transfer(transfer: &TransferRequest) -> Option<Self> {
+        match &transfer.status {
+            TransferStatus::Pending => None,
+            TransferStatus::Confirmed {
+                signature,
+                donor,
+                confirmed_at,
+            } => Some(Self {
+                signature: signature.clone(),
+                donor: donor.clone(),
+                publisher: transfer.recipient.clone(),
+                project: transfer.project.clone(),
+                mint: transfer.mint.clone(),
+                amount: transfer.amount,
+                donated_at: *confirmed_at,
+            }),
+        }
+    }
+}
+
+/// The sum of the donations made in one token
+#[derive(Debug, Default, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct TokenTotal {
+    pub mint: String,
+    /// In billionths of a token, see [crate::AMOUNT_DECIMALS]
+    pub amount: u64,
+    pub donations: u64,
+}
+
+/// A donor and what they gave in one token
+#[derive(Debug, Default, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct Supporter {
+    pub donor: String,
+    pub mint: String,
+    /// In billionths of a token, see [crate::AMOUNT_DECIMALS]
+    pub amount: u64,
+    pub donations: u64,
+}
@@ -1,5 +1,20 @@
+mod donations;
+pub use donations::*;
+
 mod projects;
 pub use projects::*;
 
+mod requests;
+pub use requests::*;
+
+mod session;
+pub use session::*;
+
+mod transfers;
+pub use transfers::*;
+
 mod utils;
 pub use utils::*;
+
+mod validation;
+pub use validation::*;
@@ -1,4 +1,7 @@
-use std::fmt::{Debug, Display};
+use std::{
+    fmt::{Debug, Display},
+    str::FromStr,
+};
 
 use ed25519_dalek::VerifyingKey;
 use serde::{Deserialize, Serialize};
@@ -33,6 +36,64 @@ impl Project {
         self.publisher.2.as_str()
     }
 
+    /// The lowercase alphanumeric words of `name` joined by hyphens
+    pub fn slugify(name: &str) -> String {
+        name.split(|character: char| !character.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(|word| word.to_lowercase())
+            .collect::<Vec<String>>()
+            .join("-")
+    }
+
+    pub fn slug(&self) -> String {
+        Self::slugify(&self.name)
+    }
+
+    /// The id given to a new project, it is kept when the project is renamed
+    pub fn derive_id(publisher_address: &str, slug: &str) -> blake3::Hash {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(publisher_address.as_bytes());
+        hasher.update(b"/");
+        hasher.update(slug.as_bytes());
+
+        hasher.finalize()
+    }
+
+    /// The human readable path of the project, unique among the projects of a publisher
+    pub fn alias(&self) -> String {
+        String::new() + self.publisher_address() + "/" + self.slug().as_str()
+    }
+
+    pub fn id_hex(&self) -> String {
+        Self::id_to_hex(&self.id)
+    }
+
+    pub fn id_base58(&self) -> String {
+        Self::id_to_base58(&self.id)
+    }
+
+    pub fn id_to_hex(id: &blake3::Hash) -> String {
+        id.to_hex().to_string()
+    }
+
+    pub fn id_to_base58(id: &blake3::Hash) -> String {
+        bs58::encode(id.as_bytes()).into_string()
+    }
+
+    /// Parses an id encoded as hex or base58
+    pub fn parse_id(value: &str) -> Option<blake3::Hash> {
+        if let Ok(id) = blake3::Hash::from_hex(value) {
+            return Some(id);
+        }
+
+        let mut bytes = [0u8; blake3::OUT_LEN];
+        if bs58::decode(value).onto(&mut bytes).ok()? != blake3::OUT_LEN {
+            return None;
+        }
+
+        Some(blake3::Hash::from_bytes(bytes))
+    }
+
     pub fn name_short(&self) -> String {
         let len = self.name.len();
         let mut name = self.name.clone();
@@ -61,13 +122,13 @@ impl Default for Project {
                 publisher.address(),
                 publisher.name,
             ),
-            codebase: "foo://bar.codebase".to_string(),
-            website: Option::Some("foo://bar.webzite".to_string()),
-            docs: "foo://docs.code.base-docs".to_string(),
+            codebase: "https://example.org/bar/codebase".to_string(),
+            website: Option::Some("https://bar.example.org".to_string()),
+            docs: "https://docs.example.org/bar".to_string(),
             phishing: vec![Phishing::default()],
             category: ProjectCategory::default(),
             language: CodeLanguage::default(),
-            package_uri: "foo://packaging-example".to_string(),
+            package_uri: "https://example.org/packages/bar".to_string(),
             description: "Default example for a project".to_string(),
         }
     }
@@ -84,6 +145,9 @@ pub struct Publisher {
     pub public_key: VerifyingKey,
     pub mint: VerifyingKey,
     pub merch: Vec<Merch>,
+    /// Keys other than [Publisher::public_key] allowed to act for the publisher
+    #[serde(default)]
+    pub signers: Vec<PublisherSigner>,
 }
 
 impl Publisher {
@@ -92,12 +156,21 @@ impl Publisher {
     }
 
     pub fn short_address(&self) -> String {
-        let address = self.address().clone();
-        let mut short_address = String::default();
+        Self::shorten(&self.address())
+    }
 
-        short_address.push_str(&address[..5]);
+    /// The first 5 and last 4 characters of `address`, or all of it if it is
+    /// too short to shorten
+    pub fn shorten(address: &str) -> String {
+        let chars = address.chars().collect::<Vec<char>>();
+
+        if chars.len() <= 12 {
+            return address.to_string();
+        }
+
+        let mut short_address = chars[..5].iter().collect::<String>();
         short_address.push_str("...");
-        short_address.push_str(&address[address.len() - 4..]);
+        short_address.extend(&chars[chars.len() - 4..]);
 
         short_address
     }
@@ -111,6 +184,47 @@ impl Publisher {
 
         bs58::encode(&public_key.to_bytes()).into_string()
     }
+
+    /// The names of the fields that differ from `previous`
+    pub fn changed_fields(&self, previous: &Publisher) -> Vec<&'static str> {
+        [
+            ("name", self.name != previous.name),
+            ("icon", self.icon != previous.icon),
+            ("logo", self.logo != previous.logo),
+            ("description", self.description != previous.description),
+            ("codebase", self.codebase != previous.codebase),
+            ("website", self.website != previous.website),
+            ("public key", self.public_key != previous.public_key),
+            ("mint", self.mint != previous.mint),
+            ("merch", self.merch != previous.merch),
+            ("signers", self.signers != previous.signers),
+        ]
+        .into_iter()
+        .filter_map(|(field, changed)| changed.then_some(field))
+        .collect()
+    }
+
+    /// The role of `key`, the key the publisher is registered under is always an owner
+    pub fn role_of(&self, key: &VerifyingKey) -> Option<SignerRole> {
+        if &self.public_key == key {
+            return Some(SignerRole::Owner);
+        }
+
+        self.signers
+            .iter()
+            .find(|signer| &signer.public_key == key)
+            .map(|signer| signer.role)
+    }
+
+    pub fn base58_to_public_key(address: &str) -> Option<VerifyingKey> {
+        let mut bytes = [0u8; 32];
+
+        if bs58::decode(address).onto(&mut bytes).ok()? != 32 {
+            return None;
+        }
+
+        VerifyingKey::from_bytes(&bytes).ok()
+    }
 }
 
 impl Debug for Publisher {
@@ -125,6 +239,7 @@ impl Debug for Publisher {
             .field("public_key", &self.address())
             .field("mint", &self.mint_address())
             .field("merch", &self.merch)
+            .field("signers", &self.signers)
             .finish()
     }
 }
@@ -136,11 +251,12 @@ impl Default for Publisher {
             icon: "https://picsum.photos/100".to_string(),
             logo: "https://picsum.photos/100".to_string(),
             description: "Default org description".to_string(),
-            codebase: "foo://code.base".to_string(),
-            website: "foo://example.project".to_string(),
+            codebase: "https://example.org/foo/codebase".to_string(),
+            website: "https://foo.example.org".to_string(),
             public_key: VerifyingKey::from_bytes(&[0u8; 32]).unwrap(),
             mint: VerifyingKey::from_bytes(&[0u8; 32]).unwrap(),
             merch: vec![Merch::default()],
+            signers: Vec::default(),
         }
     }
 }
@@ -209,6 +325,21 @@ pub enum ProjectCategory {
     Unspecified,
 }
 
+impl ProjectCategory {
+    /// Every variant in declaration order, used to build filter options
+    pub const fn all() -> &'static [Self] {
+        &[
+            Self::Cryptography,
+            Self::Ai,
+            Self::Graphics,
+            Self::Text,
+            Self::Wallet,
+            Self::Payments,
+            Self::Unspecified,
+        ]
+    }
+}
+
 impl Display for ProjectCategory {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -227,6 +358,23 @@ impl Display for ProjectCategory {
     }
 }
 
+impl FromStr for ProjectCategory {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "Cryptography" => Ok(Self::Cryptography),
+            "AI" | "Ai" => Ok(Self::Ai),
+            "Wallet" => Ok(Self::Wallet),
+            "Payments" => Ok(Self::Payments),
+            "Graphics" => Ok(Self::Graphics),
+            "Text" => Ok(Self::Text),
+            "Unspecified" => Ok(Self::Unspecified),
+            _ => Err(format!("Unknown project category `{value}`")),
+        }
+    }
+}
+
 #[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Clone, Serialize, Deserialize)]
 pub enum CodeLanguage {
     #[default]
@@ -239,6 +387,21 @@ pub enum CodeLanguage {
     KotlinJava,
 }
 
+impl CodeLanguage {
+    /// Every variant in declaration order, used to build filter options
+    pub const fn all() -> &'static [Self] {
+        &[
+            Self::NonCode,
+            Self::Rust,
+            Self::TypeScript,
+            Self::Go,
+            Self::Python,
+            Self::JavaScript,
+            Self::KotlinJava,
+        ]
+    }
+}
+
 impl Display for CodeLanguage {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -256,3 +419,79 @@ impl Display for CodeLanguage {
         )
     }
 }
+
+impl FromStr for CodeLanguage {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "NonCode" => Ok(Self::NonCode),
+            "Rust" => Ok(Self::Rust),
+            "TypeScript" => Ok(Self::TypeScript),
+            "Go" => Ok(Self::Go),
+            "Python" => Ok(Self::Python),
+            "JavaScript" => Ok(Self::JavaScript),
+            "KotlinJava" => Ok(Self::KotlinJava),
+            _ => Err(format!("Unknown code language `{value}`")),
+        }
+    }
+}
+
+/// A page of the project listing. `next_cursor` is passed back as the `cursor`
+/// query parameter to fetch the next page and is `None` on the last page.
+/// `skipped` holds the keys of records that could not be decoded.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct ProjectPage {
+    pub projects: Vec<Project>,
+    pub next_cursor: Option<String>,
+    pub skipped: Vec<String>,
+}
+
+/// What a key of [Publisher::signers] may do. Editors maintain the profile
+/// and projects, owners may also change the signers, mint and key.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize, Deserialize)]
+pub enum SignerRole {
+    Editor,
+    Owner,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct PublisherSigner {
+    pub public_key: VerifyingKey,
+    pub role: SignerRole,
+}
+
+/// Moves the publisher at `address` to `new_key`, which must already be one
+/// of its owners. Signed by the current key of the publisher it happens at
+/// once, otherwise once more than half of its owners signed the same rotation
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct KeyRotation {
+    pub address: String,
+    pub new_key: VerifyingKey,
+}
+
+/// A version of a publisher profile as it was written
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct PublisherRevision {
+    pub publisher: Publisher,
+    /// Unix timestamp of the signed request that wrote this version, `None`
+    /// for profiles registered before their history was kept
+    pub edited_at: Option<u64>,
+    /// The key that signed the change
+    pub signer: VerifyingKey,
+}
+
+/// Registers `publisher` together with its first project, either both are
+/// stored or neither is
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct Onboarding {
+    pub publisher: Publisher,
+    pub project: Project,
+}
+
+/// Ranked results of a full-text search, best match first
+#[derive(Debug, Default, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct SearchResults {
+    pub projects: Vec<Project>,
+    pub publishers: Vec<Publisher>,
+}
@@ -0,0 +1,166 @@
+use std::fmt;
+
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+use crate::Publisher;
+
+/// Number of seconds a [SignedRequest] is accepted for after its timestamp
+pub const SIGNED_REQUEST_VALIDITY_SECS: u64 = 300;
+
+/// Number of seconds a [SignedRequest] timestamp may be ahead of the server clock
+pub const SIGNED_REQUEST_CLOCK_SKEW_SECS: u64 = 30;
+
+const SIGNED_REQUEST_DOMAIN: &[u8] = b"TRENCHESFUND SIGNED REQUEST V1";
+
+/// The authentication envelope for every mutating call to the backend.
+/// The `signature` is made by `signer` over [SignedRequest::signing_bytes]
+/// which binds the payload to a single use `nonce` and a `timestamp`
+/// in seconds since the UNIX epoch.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct SignedRequest<T> {
+    pub payload: T,
+    pub signer: VerifyingKey,
+    pub nonce: [u8; 32],
+    pub timestamp: u64,
+    pub signature: Signature,
+}
+
+impl<T: Serialize> SignedRequest<T> {
+    pub fn new(
+        payload: T,
+        signer: VerifyingKey,
+        nonce: [u8; 32],
+        timestamp: u64,
+        signature: Signature,
+    ) -> Self {
+        Self {
+            payload,
+            signer,
+            nonce,
+            timestamp,
+            signature,
+        }
+    }
+
+    /// Signs the request with a local keypair instead of a wallet
+    pub fn sign(
+        payload: T,
+        signing_key: &SigningKey,
+        nonce: [u8; 32],
+        timestamp: u64,
+    ) -> Result<Self, SignedRequestError> {
+        let signer = signing_key.verifying_key();
+        let message = Self::signing_bytes(&payload, &signer, &nonce, timestamp)?;
+        let signature = signing_key.sign(&message);
+
+        Ok(Self::new(payload, signer, nonce, timestamp, signature))
+    }
+
+    /// The canonical bytes to sign. This is a domain separator followed by the
+    /// bincode encoding of `(payload, signer, nonce, timestamp)`
+    pub fn signing_bytes(
+        payload: &T,
+        signer: &VerifyingKey,
+        nonce: &[u8; 32],
+        timestamp: u64,
+    ) -> Result<Vec<u8>, SignedRequestError> {
+        let mut message = SIGNED_REQUEST_DOMAIN.to_vec();
+        bincode::serialize_into(&mut message, &(payload, signer, nonce, timestamp))
+            .or(Err(SignedRequestError::Encoding))?;
+
+        Ok(message)
+    }
+
+    pub fn message(&self) -> Result<Vec<u8>, SignedRequestError> {
+        Self::signing_bytes(&self.payload, &self.signer, &self.nonce, self.timestamp)
+    }
+
+    /// Checks the signature only, use [Self::check_timestamp] for expiry
+    pub fn verify(&self) -> Result<(), SignedRequestError> {
+        self.signer
+            .verify_strict(&self.message()?, &self.signature)
+            .or(Err(SignedRequestError::InvalidSignature))
+    }
+
+    /// Checks that the timestamp is within the validity window relative to `now`
+    pub fn check_timestamp(&self, now: u64) -> Result<(), SignedRequestError> {
+        if self.timestamp > now.saturating_add(SIGNED_REQUEST_CLOCK_SKEW_SECS)
+            || now > self.timestamp.saturating_add(SIGNED_REQUEST_VALIDITY_SECS)
+        {
+            Err(SignedRequestError::Expired)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// The time in seconds after which the nonce no longer needs to be tracked
+    pub fn expiry(&self) -> u64 {
+        self.timestamp.saturating_add(SIGNED_REQUEST_VALIDITY_SECS)
+    }
+
+    pub fn signer_address(&self) -> String {
+        Publisher::public_key_to_base58(&self.signer)
+    }
+
+    /// The key a server uses to remember this nonce has been used by the signer
+    pub fn nonce_key(&self) -> String {
+        self.signer_address() + ":" + &bs58::encode(&self.nonce).into_string()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SignedRequestError {
+    Encoding,
+    InvalidSignature,
+    Expired,
+}
+
+impl fmt::Display for SignedRequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Encoding => "Unable to encode the signed request",
+                Self::InvalidSignature => "The signature is invalid for the payload provided",
+                Self::Expired => "The signed request has expired",
+            }
+        )
+    }
+}
+
+impl std::error::Error for SignedRequestError {}
+
+/// Header based request signing for routes that authenticate the caller as a
+/// [Publisher]. The signer signs [SignedHeaders::message] and sends the
+/// values in the headers named by the constants of this type.
+pub struct SignedHeaders;
+
+impl SignedHeaders {
+    /// The base58 address of the signer
+    pub const SIGNER: &'static str = "X-Trenches-Signer";
+    /// Seconds since the UNIX epoch
+    pub const TIMESTAMP: &'static str = "X-Trenches-Timestamp";
+    /// The hex encoded BLAKE3 hash of the request body
+    pub const BODY_HASH: &'static str = "X-Trenches-Body-Hash";
+    /// The base58 encoded signature
+    pub const SIGNATURE: &'static str = "X-Trenches-Signature";
+
+    const DOMAIN: &'static [u8] = b"TRENCHESFUND SIGNED HEADERS V1";
+
+    /// The canonical bytes to sign, `path` includes the query string if any
+    pub fn message(method: &str, path: &str, body_hash: &blake3::Hash, timestamp: u64) -> Vec<u8> {
+        let mut message = Self::DOMAIN.to_vec();
+
+        [method.as_bytes(), path.as_bytes(), body_hash.as_bytes()]
+            .iter()
+            .for_each(|part| {
+                message.extend_from_slice(&(part.len() as u64).to_le_bytes());
+                message.extend_from_slice(part);
+            });
+        message.extend_from_slice(&timestamp.to_le_bytes());
+
+        message
+    }
+}
@@ -0,0 +1,43 @@
+use ed25519_dalek::Signature;
+use serde::{Deserialize, Serialize};
+
+/// The Sign-In-With-Solana message a wallet signs to start a [Session]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct SignInChallenge {
+    pub address: String,
+    /// Tells this challenge apart from the other pending ones of the address
+    pub nonce: String,
+    pub message: String,
+    pub expires_at: u64,
+}
+
+impl SignInChallenge {
+    pub fn new(domain: &str, address: &str, nonce: &str, issued_at: u64, expires_at: u64) -> Self {
+        let message = format!(
+            "{domain} wants you to sign in with your Solana account:\n{address}\n\nSign in to Trenches Fund\n\nNonce: {nonce}\nIssued At: {issued_at}\nExpiration Time: {expires_at}"
+        );
+
+        Self {
+            address: address.to_string(),
+            nonce: nonce.to_string(),
+            message,
+            expires_at,
+        }
+    }
+}
+
+/// The signature by `address` over [SignInChallenge::message]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct SignIn {
+    pub address: String,
+    /// The [SignInChallenge::nonce] of the challenge answered
+    pub nonce: String,
+    pub signature: Signature,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub token: String,
+    pub address: String,
+    pub expires_at: u64,
+}
@@ -0,0 +1,180 @@
+use serde::{Deserialize, Serialize};
+
+/// Amounts are counted in billionths of a token, enough for SPL tokens of up
+/// to 9 decimals and for lamports
+pub const AMOUNT_DECIMALS: u32 = 9;
+
+/// The base58 mint of a publisher that takes donations in SOL instead of an SPL token
+pub const NATIVE_MINT: &str = "11111111111111111111111111111111";
+
+/// Parses a decimal token amount like `1.25` into billionths of a token
+pub fn parse_amount(amount: &str) -> Option<u64> {
+    let (whole, fraction) = amount.trim().split_once('.').unwrap_or((amount.trim(), ""));
+
+    if (whole.is_empty() && fraction.is_empty())
+        || fraction.len() > AMOUNT_DECIMALS as usize
+        || !whole
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+
+    let whole = if whole.is_empty() {
+        0
+    } else {
+        whole.parse::<u64>().ok()?
+    };
+    let fraction = format!("{fraction:0<width$}", width = AMOUNT_DECIMALS as usize)
+        .parse::<u64>()
+        .ok()?;
+
+    whole
+        .checked_mul(10u64.pow(AMOUNT_DECIMALS))?
+        .checked_add(fraction)
+}
+
+/// Formats billionths of a token as a decimal amount without trailing zeros
+pub fn format_amount(amount: u64) -> String {
+    let unit = 10u64.pow(AMOUNT_DECIMALS);
+    let whole = amount / unit;
+    let fraction = amount % unit;
+
+    if fraction == 0 {
+        return whole.to_string();
+    }
+
+    let fraction = format!("{fraction:0>width$}", width = AMOUNT_DECIMALS as usize);
+
+    format!("{whole}.{}", fraction.trim_end_matches('0'))
+}
+
+/// Percent-encodes everything but the unreserved characters of RFC 3986
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+/// A donor asking for a Solana Pay transfer request to a publisher
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct DonationRequest {
+    /// The base58 address of the publisher
+    pub publisher: String,
+    /// The base58 id of the project the donation is for, if any
+    pub project: Option<String>,
+    /// In billionths of a token, see [AMOUNT_DECIMALS]
+    pub amount: u64,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub enum TransferStatus {
+    /// No transaction referencing the request has been seen yet
+    Pending,
+    /// A confirmed transaction referencing the request paid at least its amount
+    Confirmed {
+        signature: String,
+        /// The fee payer of the transaction
+        donor: String,
+        confirmed_at: u64,
+    },
+}
+
+/// A Solana Pay transfer request, told apart from every other by its `reference` key
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct TransferRequest {
+    /// The base58 public key added to the transfer so it can be found on chain
+    pub reference: String,
+    /// The base58 address of the publisher receiving the transfer
+    pub recipient: String,
+    /// The base58 mint of the token paid, [NATIVE_MINT] for SOL
+    pub mint: String,
+    /// In billionths of a token, see [AMOUNT_DECIMALS]
+    pub amount: u64,
+    pub project: Option<String>,
+    pub label: String,
+    pub message: String,
+    pub requested_at: u64,
+    pub status: TransferStatus,
+}
+
+impl TransferRequest {
+    pub fn is_native(&self) -> bool {
+        self.mint == NATIVE_MINT
+    }
+
+    pub fn is_confirmed(&self) -> bool {
+        matches!(self.status, TransferStatus::Confirmed { .. })
+    }
+
+    /// The `solana:` URL wallets scan to pay the request
+    pub fn url(&self) -> String {
+        let mut url = String::from("solana:") + &self.recipient;
+        url.push_str("?amount=");
+        url.push_str(&format_amount(self.amount));
+
+        if !self.is_native() {
+            url.push_str("&spl-token=");
+            url.push_str(&self.mint);
+        }
+
+        url.push_str("&reference=");
+        url.push_str(&self.reference);
+        url.push_str("&label=");
+        url.push_str(&percent_encode(&self.label));
+        url.push_str("&message=");
+        url.push_str(&percent_encode(&self.message));
+
+        url
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_formats_amounts() {
+        assert_eq!(parse_amount("1.25"), Some(1_250_000_000));
+        assert_eq!(parse_amount(".5"), Some(500_000_000));
+        assert_eq!(parse_amount("0.000000001"), Some(1));
+        assert_eq!(parse_amount("0.0000000001"), None);
+        assert_eq!(parse_amount("1,5"), None);
+        assert_eq!(parse_amount("."), None);
+        assert_eq!(parse_amount("99999999999"), None);
+
+        assert_eq!(format_amount(1_250_000_000), "1.25");
+        assert_eq!(format_amount(3_000_000_000), "3");
+        assert_eq!(format_amount(1), "0.000000001");
+    }
+
+    #[test]
+    fn builds_the_solana_pay_url() {
+        let mut request = TransferRequest {
+            reference: "Ref".to_string(),
+            recipient: "Recipient".to_string(),
+            mint: "Mint".to_string(),
+            amount: 1_500_000_000,
+            project: None,
+            label: "Foo Organization".to_string(),
+            message: "Thanks & cheers".to_string(),
+            requested_at: 0,
+            status: TransferStatus::Pending,
+        };
+
+        assert_eq!(
+            request.url(),
+            "solana:Recipient?amount=1.5&spl-token=Mint&reference=Ref&label=Foo%20Organization&message=Thanks%20%26%20cheers"
+        );
+
+        request.mint = NATIVE_MINT.to_string();
+        assert!(!request.url().contains("spl-token"));
+    }
+}
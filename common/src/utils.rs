@@ -1,9 +1,75 @@
+use std::fmt;
+
 use serde::{Deserialize, Serialize};
 
+use crate::FieldError;
+
 pub const REST_ENDPOINT: &str = "https://inthetrenches.cloud:443";
 
-#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
-pub enum Outcome<T> {
-    Success(T),
-    Failure(String),
+/// Machine readable reason a request to the backend failed
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum ErrorCode {
+    /// The request body or parameters are malformed
+    BadRequest,
+    /// No credentials were provided
+    Unauthorized,
+    InvalidPublicKey,
+    InvalidSignature,
+    RequestExpired,
+    NonceReused,
+    SessionExpired,
+    /// The caller is authenticated but does not own the resource
+    PermissionDenied,
+    NotFound,
+    AlreadyExists,
+    /// A concurrent request changed the same records, retrying may succeed
+    Conflict,
+    /// Too many requests of this kind are waiting, retrying later may succeed
+    TooManyRequests,
+    Internal,
+    /// Client side only, the backend could not be reached or sent an unreadable response
+    Network,
+    /// Client side only, no wallet is connected or it declined to sign
+    Wallet,
+}
+
+/// The body of every non-2xx response from the backend
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct ApiError {
+    pub code: ErrorCode,
+    pub message: String,
+    /// The fields that failed validation, if that is why the request was rejected
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldError>,
+}
+
+impl ApiError {
+    pub fn new(code: ErrorCode, message: impl fmt::Display) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+            fields: Vec::default(),
+        }
+    }
+
+    /// A `BadRequest` listing the fields that failed validation
+    pub fn invalid_fields(fields: Vec<FieldError>) -> Self {
+        Self {
+            code: ErrorCode::BadRequest,
+            message: fields
+                .iter()
+                .map(|field| field.to_string())
+                .collect::<Vec<String>>()
+                .join(", "),
+            fields,
+        }
+    }
 }
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ApiError {}
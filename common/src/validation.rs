@@ -0,0 +1,300 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{DonationRequest, KeyRotation, Merch, Phishing, Project, Publisher};
+
+/// Longest name of a publisher, project, merch item or phishing entry
+pub const MAX_NAME_LEN: usize = 64;
+/// Longest description or analysis
+pub const MAX_DESCRIPTION_LEN: usize = 2000;
+/// Longest URL
+pub const MAX_URL_LEN: usize = 512;
+
+/// A field that failed validation. Nested fields are named by their path,
+/// e.g. `merch[0].image`.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, message: impl fmt::Display) -> Self {
+        Self {
+            field: field.into(),
+            message: message.to_string(),
+        }
+    }
+
+    /// The message of the first error reported for `field`
+    pub fn message_for(errors: &[Self], field: &str) -> Option<String> {
+        errors
+            .iter()
+            .find(|error| error.field == field)
+            .map(|error| error.message.clone())
+    }
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// The rules every stored record follows, checked by the backend before a
+/// write and by the frontend forms before signing
+pub trait Validate {
+    fn validate(&self) -> Result<(), Vec<FieldError>>;
+}
+
+/// Checks that `value` is a base58 encoded Ed25519 public key
+pub fn validate_address(field: &str, value: &str) -> Result<(), FieldError> {
+    match Publisher::base58_to_public_key(value) {
+        Some(_) => Ok(()),
+        None => Err(FieldError::new(
+            field,
+            "Must be a base58 encoded Ed25519 public key",
+        )),
+    }
+}
+
+/// Collects the errors of one record
+#[derive(Debug, Default)]
+struct Checks(Vec<FieldError>);
+
+impl Checks {
+    fn fail(&mut self, field: &str, message: impl fmt::Display) {
+        self.0.push(FieldError::new(field, message));
+    }
+
+    fn max_len(&mut self, field: &str, value: &str, max: usize) -> bool {
+        if value.chars().count() > max {
+            self.fail(field, format!("Must be at most {max} characters"));
+
+            return false;
+        }
+
+        true
+    }
+
+    fn name(&mut self, field: &str, value: &str) {
+        if value.trim().is_empty() {
+            self.fail(field, "Must not be empty");
+        } else {
+            self.max_len(field, value, MAX_NAME_LEN);
+        }
+    }
+
+    fn description(&mut self, field: &str, value: &str) {
+        self.max_len(field, value, MAX_DESCRIPTION_LEN);
+    }
+
+    /// An `https` URL with a host, links are optional so an empty value passes
+    fn url(&mut self, field: &str, value: &str) {
+        if value.is_empty() || !self.max_len(field, value, MAX_URL_LEN) {
+            return;
+        }
+
+        let host = value
+            .strip_prefix("https://")
+            .map(|rest| rest.split(['/', '?', '#']).next().unwrap_or_default());
+
+        match host {
+            None => self.fail(field, "Must be an https:// link"),
+            Some(host) if host.is_empty() || value.contains(char::is_whitespace) => {
+                self.fail(field, "Must be a valid link")
+            }
+            Some(_) => (),
+        }
+    }
+
+    /// Any non empty link, phishing sites are not limited to https
+    fn link(&mut self, field: &str, value: &str) {
+        if value.trim().is_empty() {
+            self.fail(field, "Must not be empty");
+        } else {
+            self.max_len(field, value, MAX_URL_LEN);
+        }
+    }
+
+    fn address(&mut self, field: &str, value: &str) {
+        if let Err(error) = validate_address(field, value) {
+            self.0.push(error);
+        }
+    }
+
+    fn merch(&mut self, field: &str, merch: &Merch) {
+        self.name(&format!("{field}.name"), &merch.name);
+        self.name(&format!("{field}.supplier"), &merch.supplier);
+        self.url(&format!("{field}.image"), &merch.image);
+        self.description(&format!("{field}.description"), &merch.description);
+    }
+
+    fn phishing(&mut self, field: &str, phishing: &Phishing) {
+        self.name(&format!("{field}.name"), &phishing.name);
+        self.link(&format!("{field}.uri"), &phishing.uri);
+        self.description(&format!("{field}.analysis"), &phishing.analysis);
+
+        for (index, (name, uri)) in phishing.other_uri.iter().enumerate() {
+            self.name(&format!("{field}.other_uri[{index}].name"), name);
+            self.link(&format!("{field}.other_uri[{index}].uri"), uri);
+        }
+    }
+
+    fn finish(self) -> Result<(), Vec<FieldError>> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(self.0)
+        }
+    }
+}
+
+impl Validate for Publisher {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut checks = Checks::default();
+
+        checks.name("name", &self.name);
+        checks.url("icon", &self.icon);
+        checks.url("logo", &self.logo);
+        checks.description("description", &self.description);
+        checks.url("codebase", &self.codebase);
+        checks.url("website", &self.website);
+
+        for (index, merch) in self.merch.iter().enumerate() {
+            checks.merch(&format!("merch[{index}]"), merch);
+        }
+
+        checks.finish()
+    }
+}
+
+impl Validate for Project {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut checks = Checks::default();
+
+        checks.name("name", &self.name);
+        if !self.name.trim().is_empty() && self.slug().is_empty() {
+            checks.fail("name", "Must contain a letter or a digit");
+        }
+        checks.url("logo", &self.logo);
+        checks.url("icon", &self.icon);
+        checks.address("publisher", self.publisher_address());
+        checks.url("codebase", &self.codebase);
+        checks.url("website", self.website.as_deref().unwrap_or_default());
+        checks.url("docs", &self.docs);
+        checks.url("package_uri", &self.package_uri);
+        checks.description("description", &self.description);
+
+        for (index, phishing) in self.phishing.iter().enumerate() {
+            checks.phishing(&format!("phishing[{index}]"), phishing);
+        }
+
+        checks.finish()
+    }
+}
+
+impl Validate for KeyRotation {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut checks = Checks::default();
+
+        checks.address("address", &self.address);
+
+        checks.finish()
+    }
+}
+
+impl Validate for DonationRequest {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut checks = Checks::default();
+
+        checks.address("publisher", &self.publisher);
+        if let Some(project) = self.project.as_deref() {
+            if bs58::decode(project).into_vec().map(|id| id.len()) != Ok(blake3::OUT_LEN) {
+                checks.fail("project", "Must be a base58 encoded project id");
+            }
+        }
+        if self.amount == 0 {
+            checks.fail("amount", "Must be more than zero");
+        }
+
+        checks.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(errors: Vec<FieldError>) -> Vec<String> {
+        errors.into_iter().map(|error| error.field).collect()
+    }
+
+    #[test]
+    fn accepts_the_defaults() {
+        assert_eq!(Publisher::default().validate(), Ok(()));
+        assert_eq!(Project::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn limits_urls_to_https() {
+        let publisher = Publisher {
+            codebase: "http://example.org".to_string(),
+            website: "https://".to_string(),
+            icon: "https://exa mple.org/icon.png".to_string(),
+            logo: String::new(),
+            ..Publisher::default()
+        };
+
+        assert_eq!(
+            fields(publisher.validate().unwrap_err()),
+            vec!["icon", "codebase", "website"]
+        );
+    }
+
+    #[test]
+    fn checks_names_and_lengths() {
+        let publisher = Publisher {
+            name: " ".to_string(),
+            description: "a".repeat(MAX_DESCRIPTION_LEN + 1),
+            merch: vec![Merch {
+                name: "a".repeat(MAX_NAME_LEN + 1),
+                ..Merch::default()
+            }],
+            ..Publisher::default()
+        };
+
+        assert_eq!(
+            fields(publisher.validate().unwrap_err()),
+            vec!["name", "description", "merch[0].name"]
+        );
+    }
+
+    #[test]
+    fn checks_project_fields() {
+        let project = Project {
+            name: "***".to_string(),
+            publisher: (String::new(), "not-a-key".to_string(), String::new()),
+            docs: "docs.example.org".to_string(),
+            phishing: vec![Phishing {
+                uri: String::new(),
+                ..Phishing::default()
+            }],
+            ..Project::default()
+        };
+
+        assert_eq!(
+            fields(project.validate().unwrap_err()),
+            vec!["name", "publisher", "docs", "phishing[0].uri"]
+        );
+    }
+
+    #[test]
+    fn shortens_any_address_without_panicking() {
+        let publisher = Publisher::default();
+        assert_eq!(publisher.short_address().len(), 12);
+
+        assert_eq!(Publisher::shorten("abc"), "abc");
+    }
+}
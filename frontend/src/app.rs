@@ -4,7 +4,10 @@ use dioxus::prelude::*;
 use gloo_timers::callback::Timeout;
 use wallet_adapter::{ConnectionInfo, WalletAdapter};
 
-use crate::{Dashboard, Explore, Footer, Header, Home, OrganizationView, ProjectView};
+use crate::{
+    sync_session, Dashboard, Explore, Footer, Header, Home, Onboarding, OrganizationView,
+    ProjectView,
+};
 
 const FAVICON: Asset = asset!("/assets/favicon.png");
 const TAILWIND_CSS: Asset = asset!("/assets/tailwind.css");
@@ -27,6 +30,7 @@ pub(crate) fn App() -> Element {
         while let Ok(wallet_event) = wallet_event_listener.recv().await {
             let connection_info = (*WALLET_ADAPTER.read().connection_info().await).clone();
             *ACTIVE_CONNECTION.write() = connection_info;
+            sync_session();
 
             GLOBAL_MESSAGE
                 .write()
@@ -124,8 +128,10 @@ pub enum Route {
     Home(),
     #[route("/dashboard")]
     Dashboard(),
-    #[route("/explore")]
-    Explore(),
+    #[route("/onboarding")]
+    Onboarding(),
+    #[route("/explore?:category&:language")]
+    Explore { category: String, language: String },
     #[route("/project/:..id")]
     ProjectView { id: Vec<String> },
     #[route("/organization/:..id")]
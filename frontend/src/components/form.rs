@@ -0,0 +1,39 @@
+use dioxus::prelude::*;
+
+pub(crate) const INPUT_CLASS: &str = "flex w-full bg-transparent border-true-blue border-b-2 py-1 px-1 text-white placeholder:text-blue-yonder outline-none";
+pub(crate) const BUTTON_CLASS: &str =
+    "text-white bg-true-blue hover:bg-cobalt-blue rounded-full py-1 px-4 disabled:opacity-50";
+pub(crate) const SECONDARY_BUTTON_CLASS: &str =
+    "text-true-blue border border-true-blue hover:bg-true-blue hover:text-white rounded-full py-1 px-4";
+
+#[component]
+pub fn TextField(
+    label: String,
+    value: String,
+    oninput: EventHandler<String>,
+    #[props(default)] multiline: bool,
+    #[props(default)] error: Option<String>,
+) -> Element {
+    rsx! {
+        label { class: "flex flex-col w-full gap-1 text-sm text-blue-200",
+            {label}
+            if multiline {
+                textarea {
+                    class: INPUT_CLASS,
+                    rows: 4,
+                    value,
+                    oninput: move |event| oninput.call(event.value()),
+                }
+            } else {
+                input {
+                    class: INPUT_CLASS,
+                    value,
+                    oninput: move |event| oninput.call(event.value()),
+                }
+            }
+            if let Some(error) = error {
+                span { class: "text-red-400 text-xs", {error} }
+            }
+        }
+    }
+}
@@ -1,9 +1,9 @@
 use dioxus::prelude::*;
 
 use crate::{
-    utils::copied_address, ChangeWalletSvg, CloseSvg, CopySvg, DisconnectSvg, GradientWalletIcon,
-    Loader, NotificationInfo, Route, WalletSvg, ACTIVE_CONNECTION, GLOBAL_MESSAGE, LOGO,
-    WALLET_ADAPTER,
+    sign_in, sign_out, utils::copied_address, ChangeWalletSvg, CloseSvg, CopySvg, DisconnectSvg,
+    GradientWalletIcon, Loader, NotificationInfo, Route, WalletSvg, ACTIVE_CONNECTION,
+    GLOBAL_MESSAGE, LOGO, SESSION, WALLET_ADAPTER,
 };
 
 #[component]
@@ -19,7 +19,10 @@ pub fn Header() -> Element {
                 div{class:"p-1 flex items-center", span{class:"w-[25px]",img{src:LOGO, alt:"LOGO"}}, span {class:"text-3xl font-smooch", "Trenches" } }
                 div{ class:"flex items-center justify-around w-[80%] mx-2",
                     {NavItem(Route::Home, "Home")}
-                    {NavItem(Route::Explore, "Explore")}
+                    {NavItem(|| Route::Explore { category: String::new(), language: String::new() }, "Explore")}
+                    if ACTIVE_CONNECTION.read().connected_account().is_ok() {
+                        {NavItem(Route::Dashboard, "Dashboard")}
+                    }
                 }
                 NavWalletItem{show_modal, show_connecting, shortened_address}
             }
@@ -174,10 +177,39 @@ pub fn ActiveAccountDropDown(show_modal: Signal<bool>, shortened_address: String
 
     let disconnect_callback = move || {
         spawn(async move {
+            let _ = sign_out().await;
             WALLET_ADAPTER.write().disconnect().await;
         });
     };
 
+    let sign_in_callback = move || {
+        spawn(async move {
+            if let Err(error) = sign_in().await {
+                GLOBAL_MESSAGE
+                    .write()
+                    .push_back(NotificationInfo::error(error));
+            } else {
+                GLOBAL_MESSAGE
+                    .write()
+                    .push_back(NotificationInfo::new("Signed in"));
+            }
+        });
+    };
+
+    let sign_out_callback = move || {
+        spawn(async move {
+            if let Err(error) = sign_out().await {
+                GLOBAL_MESSAGE
+                    .write()
+                    .push_back(NotificationInfo::error(error));
+            } else {
+                GLOBAL_MESSAGE
+                    .write()
+                    .push_back(NotificationInfo::new("Signed out"));
+            }
+        });
+    };
+
     let clone_address = shortened_address.clone();
 
     let copy_callback = move || {
@@ -225,6 +257,11 @@ pub fn ActiveAccountDropDown(show_modal: Signal<bool>, shortened_address: String
 
             if *show_dropdown.read() {
                 ul {class:"w-full min-w-[130px] text-white flex flex-col absolute z-1 text-md mt-2 bg-true-blue rounded-lg shadow-xl list-none",
+                    if SESSION.read().is_some() {
+                        {DropdownItem("Sign Out", WalletSvg(), show_dropdown, sign_out_callback)}
+                    } else {
+                        {DropdownItem("Sign In", WalletSvg(), show_dropdown, sign_in_callback)}
+                    }
                     {DropdownItem("Copy Address", CopySvg(), show_dropdown, copy_callback)}
                     {DropdownItem("Change Wallet", ChangeWalletSvg(), show_dropdown, change_wallet_callback)}
                     {DropdownItem("Disconnect", DisconnectSvg(), show_dropdown, disconnect_callback)}
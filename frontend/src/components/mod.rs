@@ -1,5 +1,8 @@
 mod footer;
 pub use footer::*;
 
+mod form;
+pub use form::*;
+
 mod header;
 pub use header::*;
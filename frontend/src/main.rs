@@ -17,6 +17,12 @@ pub(crate) use svg_assets::*;
 mod dioxus_adapter;
 pub(crate) use dioxus_adapter::*;
 
+mod session;
+pub(crate) use session::*;
+
+mod signing;
+pub(crate) use signing::*;
+
 mod app;
 pub(crate) use app::*;
 
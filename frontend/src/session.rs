@@ -0,0 +1,82 @@
+use dioxus::prelude::*;
+use trenchesfund_common::{Session, SignIn, SignInChallenge, REST_ENDPOINT};
+
+use crate::{fetch_api, ACTIVE_CONNECTION, WALLET_ADAPTER};
+
+pub(crate) static SESSION: GlobalSignal<Option<Session>> = Signal::global(|| Option::None);
+
+/// Signs the challenge issued by the backend with the connected wallet
+/// and stores the resulting [Session] in [SESSION]
+pub(crate) async fn sign_in() -> Result<(), String> {
+    let address = ACTIVE_CONNECTION
+        .read()
+        .connected_account()
+        .map(|account| account.address().to_string())
+        .map_err(|error| error.to_string())?;
+
+    let endpoint = String::new() + REST_ENDPOINT + "/auth/challenge/" + address.as_str();
+    let challenge = fetch_api::<SignInChallenge>(reqwest::Client::new().post(&endpoint))
+        .await
+        .map_err(|error| error.to_string())?;
+
+    let signed_message = WALLET_ADAPTER
+        .read()
+        .sign_message(challenge.message.as_bytes())
+        .await
+        .map_err(|error| error.to_string())?;
+
+    let sign_in = SignIn {
+        address,
+        nonce: challenge.nonce,
+        signature: signed_message.signature(),
+    };
+
+    let endpoint = String::new() + REST_ENDPOINT + "/auth/login";
+    let body = serde_json::to_string(&sign_in).map_err(|error| error.to_string())?;
+    let session = fetch_api::<Session>(
+        reqwest::Client::new()
+            .post(&endpoint)
+            .header("Content-Type", "application/json")
+            .body(body),
+    )
+    .await
+    .map_err(|error| error.to_string())?;
+
+    SESSION.write().replace(session);
+
+    Ok(())
+}
+
+pub(crate) async fn sign_out() -> Result<(), String> {
+    let Some(session) = SESSION.write().take() else {
+        return Ok(());
+    };
+
+    let endpoint = String::new() + REST_ENDPOINT + "/auth/logout";
+
+    fetch_api::<()>(
+        reqwest::Client::new()
+            .post(&endpoint)
+            .bearer_auth(session.token),
+    )
+    .await
+    .map_err(|error| error.to_string())
+}
+
+/// Drops the session when the wallet disconnects or switches accounts
+pub(crate) fn sync_session() {
+    let connected_address = ACTIVE_CONNECTION
+        .read()
+        .connected_account()
+        .map(|account| account.address().to_string())
+        .ok();
+
+    let session_address = SESSION
+        .read()
+        .as_ref()
+        .map(|session| session.address.clone());
+
+    if session_address.is_some() && session_address != connected_address {
+        SESSION.write().take();
+    }
+}
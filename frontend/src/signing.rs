@@ -0,0 +1,101 @@
+use dioxus::prelude::*;
+use ed25519_dalek::{Signature, VerifyingKey};
+use serde::{de::DeserializeOwned, Serialize};
+use trenchesfund_common::{
+    ApiError, ErrorCode, Publisher, SignedHeaders, SignedRequest, REST_ENDPOINT,
+};
+use wallet_adapter::web_sys;
+
+use crate::{fetch_api, ACTIVE_CONNECTION, WALLET_ADAPTER};
+
+fn wallet_error(error: impl core::fmt::Display) -> ApiError {
+    ApiError::new(ErrorCode::Wallet, error)
+}
+
+fn unix_timestamp() -> u64 {
+    (web_sys::js_sys::Date::now() / 1000.0) as u64
+}
+
+/// The key of the account connected in [ACTIVE_CONNECTION]
+pub(crate) fn connected_key() -> Result<VerifyingKey, ApiError> {
+    let address = ACTIVE_CONNECTION
+        .read()
+        .connected_account()
+        .map(|account| account.address().to_string())
+        .map_err(wallet_error)?;
+
+    Publisher::base58_to_public_key(&address)
+        .ok_or_else(|| wallet_error("The connected account is not an ed25519 key"))
+}
+
+/// Asks the connected wallet to sign `message`
+async fn wallet_signature(message: &[u8]) -> Result<Signature, ApiError> {
+    let signed_message = WALLET_ADAPTER
+        .read()
+        .sign_message(message)
+        .await
+        .map_err(wallet_error)?;
+
+    Ok(signed_message.signature())
+}
+
+/// Wraps `payload` in a [SignedRequest] signed by the connected wallet
+pub(crate) async fn sign_request<T: Serialize>(payload: T) -> Result<SignedRequest<T>, ApiError> {
+    let signer = connected_key()?;
+    let mut nonce = [0u8; 32];
+    fastrand::fill(&mut nonce);
+    let timestamp = unix_timestamp();
+
+    let message =
+        SignedRequest::signing_bytes(&payload, &signer, &nonce, timestamp).map_err(wallet_error)?;
+    let signature = wallet_signature(&message).await?;
+
+    Ok(SignedRequest::new(
+        payload, signer, nonce, timestamp, signature,
+    ))
+}
+
+/// Signs `payload` with the connected wallet and posts it to `path`
+pub(crate) async fn send_signed<T: Serialize, R: DeserializeOwned>(
+    path: &str,
+    payload: T,
+) -> Result<R, ApiError> {
+    let request = sign_request(payload).await?;
+    let body = serde_json::to_string(&request).map_err(wallet_error)?;
+
+    fetch_api::<R>(
+        reqwest::Client::new()
+            .post(String::new() + REST_ENDPOINT + path)
+            .header("Content-Type", "application/json")
+            .body(body),
+    )
+    .await
+}
+
+/// Posts to `path` without a body, authenticated by [SignedHeaders]
+pub(crate) async fn post_with_signed_headers<R: DeserializeOwned>(
+    path: &str,
+) -> Result<R, ApiError> {
+    let signer = connected_key()?;
+    let timestamp = unix_timestamp();
+    let body_hash = blake3::hash(&[]);
+
+    let signature =
+        wallet_signature(&SignedHeaders::message("POST", path, &body_hash, timestamp)).await?;
+
+    fetch_api::<R>(
+        reqwest::Client::new()
+            .post(String::new() + REST_ENDPOINT + path)
+            .header(
+                SignedHeaders::SIGNER,
+                Publisher::public_key_to_base58(&signer),
+            )
+            .header(SignedHeaders::TIMESTAMP, timestamp.to_string())
+            .header(SignedHeaders::BODY_HASH, body_hash.to_hex().to_string())
+            .header(
+                SignedHeaders::SIGNATURE,
+                bs58::encode(signature.to_bytes()).into_string(),
+            ),
+    )
+    .await
+}
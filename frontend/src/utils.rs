@@ -1,6 +1,8 @@
 use dioxus::prelude::*;
 use qrcodegen::{QrCode, QrCodeEcc};
-use wallet_adapter::{wasm_bindgen_futures::JsFuture, WalletResult};
+use serde::{de::DeserializeOwned, Serialize};
+use trenchesfund_common::{ApiError, ErrorCode};
+use wallet_adapter::{wasm_bindgen_futures::JsFuture, web_sys, WalletResult};
 
 use crate::WINDOW;
 
@@ -17,6 +19,72 @@ pub async fn copied_address(address: &str) -> WalletResult<()> {
     Ok(())
 }
 
+/// Sends a request to the backend, a non-2xx response is decoded as an [ApiError]
+pub async fn fetch_api<T: DeserializeOwned>(
+    request: reqwest::RequestBuilder,
+) -> Result<T, ApiError> {
+    let network_error = || {
+        ApiError::new(
+            ErrorCode::Network,
+            "Unable to reach the server! Check your connection and try again",
+        )
+    };
+
+    let response = request.send().await.map_err(|_| network_error())?;
+    let is_success = response.status().is_success();
+    let body = response.text().await.map_err(|_| network_error())?;
+
+    if is_success {
+        serde_json::from_str::<T>(&body).or(Err(ApiError::new(
+            ErrorCode::Network,
+            "Invalid response from the server",
+        )))
+    } else {
+        Err(serde_json::from_str::<ApiError>(&body).unwrap_or_else(|_| network_error()))
+    }
+}
+
+fn local_storage() -> Option<web_sys::Storage> {
+    WINDOW.read().local_storage().ok().flatten()
+}
+
+/// Reads a value saved with [save_draft], `None` if it is missing or unreadable
+pub fn load_draft<T: DeserializeOwned>(key: &str) -> Option<T> {
+    let json = local_storage()?.get_item(key).ok().flatten()?;
+
+    serde_json::from_str(&json).ok()
+}
+
+/// Keeps `value` in the local storage of the browser under `key`
+pub fn save_draft<T: Serialize>(key: &str, value: &T) {
+    if let (Some(storage), Ok(json)) = (local_storage(), serde_json::to_string(value)) {
+        let _ = storage.set_item(key, &json);
+    }
+}
+
+pub fn clear_draft(key: &str) {
+    if let Some(storage) = local_storage() {
+        let _ = storage.remove_item(key);
+    }
+}
+
+/// Formats a unix timestamp in seconds as a human readable date
+pub fn format_date(timestamp: u64) -> String {
+    let date = web_sys::js_sys::Date::new_0();
+    date.set_time(timestamp as f64 * 1000.0);
+
+    date.to_date_string().into()
+}
+
+/// The unix timestamp in seconds of the first day of the current month, in UTC
+pub fn start_of_month() -> u64 {
+    let now = web_sys::js_sys::Date::new_0();
+    let start =
+        web_sys::js_sys::Date::utc(now.get_utc_full_year() as f64, now.get_utc_month() as f64);
+
+    (start / 1000.0) as u64
+}
+
 // Creates a single QR Code, then prints it to the console.
 pub fn address_qrcode(address: &str) -> WalletResult<Element> {
     let errcorlvl: QrCodeEcc = QrCodeEcc::High; // Error correction level
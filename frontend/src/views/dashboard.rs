@@ -1,11 +1,616 @@
 use dioxus::prelude::*;
+use trenchesfund_common::{
+    validate_address, ApiError, CodeLanguage, ErrorCode, FieldError, Merch, Phishing, Project,
+    ProjectCategory, ProjectPage, Publisher, Validate, REST_ENDPOINT,
+};
+
+use crate::{
+    fetch_api, post_with_signed_headers, send_signed, LoadFailed, Loader, NotificationInfo, Route,
+    TextField, ACTIVE_CONNECTION, BUTTON_CLASS, GLOBAL_MESSAGE, SECONDARY_BUTTON_CLASS,
+};
+
+/// The publisher the connected wallet signs for. Wallets registered under their
+/// own key are found directly, signers added by a publisher sign in headers to find it.
+async fn load_publisher(address: &str) -> Result<Publisher, ApiError> {
+    let endpoint = String::new() + REST_ENDPOINT + "/publisher-info/" + address;
+
+    match fetch_api::<Publisher>(reqwest::Client::new().post(&endpoint)).await {
+        Err(error) if error.code == ErrorCode::NotFound => {
+            post_with_signed_headers::<Publisher>("/publisher/me")
+                .await
+                .map_err(|error| {
+                    if error.code == ErrorCode::PermissionDenied {
+                        ApiError::new(
+                            ErrorCode::NotFound,
+                            "This wallet is not registered as a publisher",
+                        )
+                    } else {
+                        error
+                    }
+                })
+        }
+        outcome => outcome,
+    }
+}
+
+/// Every page of the projects listed by the publisher at `address`
+async fn load_projects(address: &str) -> Result<Vec<Project>, ApiError> {
+    let mut projects = Vec::<Project>::new();
+    let mut cursor = Option::<String>::None;
+
+    loop {
+        let mut endpoint =
+            String::new() + REST_ENDPOINT + "/projects?limit=100&publisher=" + address;
+        if let Some(cursor) = cursor.as_ref() {
+            endpoint.push_str("&cursor=");
+            endpoint.push_str(cursor);
+        }
+
+        let page = fetch_api::<ProjectPage>(reqwest::Client::new().get(&endpoint)).await?;
+        projects.extend(page.projects);
+
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => return Ok(projects),
+        }
+    }
+}
+
+/// Shows the error of a failed save, keeping the field errors next to their fields
+fn report_failure(error: ApiError, mut errors: Signal<Vec<FieldError>>) {
+    errors.set(error.fields.clone());
+    GLOBAL_MESSAGE
+        .write()
+        .push_back(NotificationInfo::error(error));
+}
+
+/// The errors of the fields nested under `prefix`, like the entries of a list
+fn nested_errors(errors: &[FieldError], prefix: &str) -> Vec<FieldError> {
+    errors
+        .iter()
+        .filter(|error| error.field.starts_with(prefix))
+        .cloned()
+        .collect()
+}
 
 #[component]
 pub fn Dashboard() -> Element {
+    let mut publisher = use_signal(|| Option::<Publisher>::None);
+    let mut projects = use_signal(Vec::<Project>::new);
+    let mut load_error = use_signal(|| Option::<ApiError>::None);
+    // Bumped after every saved change to load the listings again
+    let mut reload = use_signal(|| 0u32);
+
+    let connected_address = ACTIVE_CONNECTION
+        .read()
+        .connected_account()
+        .map(|account| account.address().to_string())
+        .ok();
+
+    use_effect(use_reactive!(|connected_address| {
+        let _ = reload.read();
+        load_error.set(None);
+
+        let Some(address) = connected_address else {
+            publisher.set(None);
+            return;
+        };
+
+        spawn(async move {
+            let outcome = match load_publisher(&address).await {
+                Ok(found) => load_projects(&found.address())
+                    .await
+                    .map(|listed| (found, listed)),
+                Err(error) => Err(error),
+            };
+
+            match outcome {
+                Ok((found, listed)) => {
+                    projects.set(listed);
+                    publisher.set(Some(found));
+                }
+                Err(error) => {
+                    if error.code != ErrorCode::NotFound {
+                        GLOBAL_MESSAGE
+                            .write()
+                            .push_back(NotificationInfo::error(&error));
+                    }
+                    publisher.set(None);
+                    load_error.set(Some(error));
+                }
+            }
+        });
+    }));
+
+    let on_saved = move |message: String| {
+        GLOBAL_MESSAGE
+            .write()
+            .push_back(NotificationInfo::new(message));
+        *reload.write() += 1;
+    };
+
+    rsx! {
+        div { class: "flex flex-col justify-start items-center w-full min-h-[100vh] p-5 gap-10",
+            if connected_address.is_none() {
+                div { class: "flex flex-col w-full min-h-[50vh] items-center justify-center gap-4",
+                    h1 { class: "text-3xl font-smooch", "Publisher Dashboard" }
+                    p { class: "text-blue-200 text-sm", "Connect a wallet to manage your listings" }
+                }
+            } else if let Some(error) = load_error.read().as_ref() {
+                if error.code == ErrorCode::NotFound {
+                    div { class: "flex flex-col w-full min-h-[50vh] items-center justify-center gap-4",
+                        h1 { class: "text-3xl font-smooch", "Publisher Dashboard" }
+                        p { class: "text-blue-200 text-sm", {error.message.as_str()} }
+                        Link { class: "bg-true-blue rounded-full py-2 px-4", to: Route::Onboarding(), "Start a Community" }
+                    }
+                } else {
+                    LoadFailed { error: error.clone() }
+                }
+            } else if let Some(publisher) = publisher.read().as_ref() {
+                div { class: "flex w-full items-center justify-center text-wrap mt-8",
+                    h1 { class: "text-3xl font-smooch text-wrap break-all", {publisher.name.as_str()} }
+                }
+                ProfileForm { publisher: publisher.clone(), on_saved }
+                ProjectsPanel { publisher: publisher.clone(), projects: projects.read().clone(), on_saved }
+            } else {
+                {Loader()}
+            }
+        }
+    }
+}
+
+#[component]
+fn ProfileForm(publisher: Publisher, on_saved: EventHandler<String>) -> Element {
+    let mut draft = use_signal(|| publisher.clone());
+    let mut mint_input = use_signal(|| publisher.mint_address());
+    let mut saving = use_signal(|| false);
+    let mut errors = use_signal(Vec::<FieldError>::new);
+
+    // Start over from the stored profile whenever it is reloaded
+    use_effect(use_reactive!(|publisher| {
+        mint_input.set(publisher.mint_address());
+        draft.set(publisher);
+        errors.set(Vec::default());
+    }));
+
+    let submit = move |_| {
+        let mut publisher = draft.read().clone();
+        let mut invalid = publisher.validate().err().unwrap_or_default();
+
+        match validate_address("mint", mint_input.read().trim()) {
+            Ok(()) => {
+                if let Some(mint) = Publisher::base58_to_public_key(mint_input.read().trim()) {
+                    publisher.mint = mint;
+                }
+            }
+            Err(error) => invalid.push(error),
+        }
+
+        errors.set(invalid.clone());
+        if !invalid.is_empty() {
+            return;
+        }
+
+        spawn(async move {
+            saving.set(true);
+            match send_signed::<Publisher, String>("/publisher-update", publisher).await {
+                Ok(_) => on_saved.call("Profile saved".to_string()),
+                Err(error) => report_failure(error, errors),
+            }
+            saving.set(false);
+        });
+    };
+
+    let error = move |field: &str| FieldError::message_for(&errors.read(), field);
+
+    rsx! {
+        section { class: "flex flex-col w-full max-w-screen-md gap-4 p-5 rounded-2xl shadow-soft-dark",
+            h2 { class: "text-2xl font-smooch", "Profile" }
+            TextField { label: "Name", value: draft.read().name.clone(), oninput: move |value| draft.write().name = value, error: error("name") }
+            TextField { label: "Icon URL", value: draft.read().icon.clone(), oninput: move |value| draft.write().icon = value, error: error("icon") }
+            TextField { label: "Logo URL", value: draft.read().logo.clone(), oninput: move |value| draft.write().logo = value, error: error("logo") }
+            TextField { label: "Description", value: draft.read().description.clone(), oninput: move |value| draft.write().description = value, multiline: true, error: error("description") }
+            TextField { label: "Codebase URL", value: draft.read().codebase.clone(), oninput: move |value| draft.write().codebase = value, error: error("codebase") }
+            TextField { label: "Website URL", value: draft.read().website.clone(), oninput: move |value| draft.write().website = value, error: error("website") }
+            TextField { label: "Mint address", value: mint_input.read().clone(), oninput: move |value| mint_input.set(value), error: error("mint") }
+
+            MerchEditor {
+                merch: draft.read().merch.clone(),
+                onchange: move |merch| draft.write().merch = merch,
+            }
+            for invalid in nested_errors(&errors.read(), "merch[") {
+                span { class: "text-red-400 text-xs", {invalid.to_string()} }
+            }
+
+            div { class: "flex w-full justify-end",
+                button {
+                    class: BUTTON_CLASS,
+                    disabled: *saving.read(),
+                    onclick: submit,
+                    if *saving.read() { "Saving..." } else { "Save profile" }
+                }
+            }
+        }
+    }
+}
+
+/// Adds, edits and removes the merch of a profile. Changes are kept in the
+/// profile draft until it is saved.
+#[component]
+fn MerchEditor(merch: Vec<Merch>, onchange: EventHandler<Vec<Merch>>) -> Element {
+    let empty = || Merch {
+        supplier: String::new(),
+        image: String::new(),
+        name: String::new(),
+        description: String::new(),
+    };
+    let mut item = use_signal(empty);
+    // The index of the item being edited, `None` while adding a new one
+    let mut editing = use_signal(|| Option::<usize>::None);
+
+    let listed = merch.clone();
+    let submit = move |_| {
+        let mut merch = listed.clone();
+        let entry = item.read().clone();
+
+        match *editing.read() {
+            Some(index) if index < merch.len() => merch[index] = entry,
+            _ => merch.push(entry),
+        }
+
+        onchange.call(merch);
+        item.set(empty());
+        editing.set(None);
+    };
+
+    rsx! {
+        div { class: "flex flex-col w-full gap-2 mt-5",
+            h3 { class: "text-xl font-smooch", "Merch" }
+            for (index, entry) in merch.iter().cloned().enumerate() {
+                div { class: "flex w-full items-center justify-between gap-2 text-sm",
+                    span { class: "break-all", {entry.name.as_str()} " (" {entry.supplier.as_str()} ")" }
+                    div { class: "flex gap-2",
+                        button {
+                            class: SECONDARY_BUTTON_CLASS,
+                            onclick: move |_| {
+                                item.set(entry.clone());
+                                editing.set(Some(index));
+                            },
+                            "Edit"
+                        }
+                        button {
+                            class: SECONDARY_BUTTON_CLASS,
+                            onclick: {
+                                let merch = merch.clone();
+                                move |_| {
+                                    let mut merch = merch.clone();
+                                    merch.remove(index);
+                                    onchange.call(merch);
+                                    editing.set(None);
+                                }
+                            },
+                            "Remove"
+                        }
+                    }
+                }
+            }
+            TextField { label: "Merch name", value: item.read().name.clone(), oninput: move |value| item.write().name = value }
+            TextField { label: "Supplier", value: item.read().supplier.clone(), oninput: move |value| item.write().supplier = value }
+            TextField { label: "Image URL", value: item.read().image.clone(), oninput: move |value| item.write().image = value }
+            TextField { label: "Merch description", value: item.read().description.clone(), oninput: move |value| item.write().description = value }
+            div { class: "flex w-full justify-end",
+                button {
+                    class: SECONDARY_BUTTON_CLASS,
+                    disabled: item.read().name.trim().is_empty(),
+                    onclick: submit,
+                    if editing.read().is_some() { "Update merch" } else { "Add merch" }
+                }
+            }
+        }
+    }
+}
+
+/// A blank project listed by `publisher`
+fn new_project(publisher: &Publisher) -> Project {
+    Project {
+        name: String::new(),
+        logo: String::new(),
+        icon: String::new(),
+        publisher: (
+            publisher.short_address(),
+            publisher.address(),
+            publisher.name.clone(),
+        ),
+        codebase: String::new(),
+        website: None,
+        docs: String::new(),
+        phishing: Vec::new(),
+        category: ProjectCategory::default(),
+        language: CodeLanguage::default(),
+        package_uri: String::new(),
+        description: String::new(),
+        ..Project::default()
+    }
+}
+
+#[component]
+fn ProjectsPanel(
+    publisher: Publisher,
+    projects: Vec<Project>,
+    on_saved: EventHandler<String>,
+) -> Element {
+    // The project in the form and whether it is already stored
+    let mut editing = use_signal(|| Option::<(Project, bool)>::None);
+    let mut pending_removal = use_signal(|| Option::<String>::None);
+
+    let remove = move |id: String| {
+        spawn(async move {
+            match send_signed::<String, String>("/project-remove", id).await {
+                Ok(_) => on_saved.call("Project removed".to_string()),
+                Err(error) => GLOBAL_MESSAGE
+                    .write()
+                    .push_back(NotificationInfo::error(error)),
+            }
+            pending_removal.set(None);
+        });
+    };
+
+    let new_publisher = publisher.clone();
+
     rsx! {
-        div { class: "flex flex-col justify-around items-center w-full min-h-[100vh] p-5",
-            div {
-                "DASHBOARD"
+        section { class: "flex flex-col w-full max-w-screen-md gap-4 p-5 rounded-2xl shadow-soft-dark",
+            div { class: "flex w-full items-center justify-between",
+                h2 { class: "text-2xl font-smooch", "Projects" }
+                button {
+                    class: BUTTON_CLASS,
+                    onclick: move |_| editing.set(Some((new_project(&new_publisher), false))),
+                    "New project"
+                }
+            }
+
+            if projects.is_empty() {
+                p { class: "text-blue-200 text-sm", "No projects listed yet" }
+            }
+
+            for project in projects {
+                div { class: "flex w-full items-center justify-between gap-2 text-sm",
+                    div { class: "flex flex-col",
+                        span { class: "text-lg break-all", {project.name.as_str()} }
+                        span { class: "text-blue-200", {project.category.to_string()} " · " {project.language.to_string()} }
+                    }
+                    div { class: "flex gap-2",
+                        button {
+                            class: SECONDARY_BUTTON_CLASS,
+                            onclick: {
+                                let project = project.clone();
+                                move |_| editing.set(Some((project.clone(), true)))
+                            },
+                            "Edit"
+                        }
+                        if pending_removal.read().as_deref() == Some(project.id_base58().as_str()) {
+                            button {
+                                class: BUTTON_CLASS,
+                                onclick: {
+                                    let id = project.id_base58();
+                                    move |_| remove(id.clone())
+                                },
+                                "Confirm removal"
+                            }
+                        } else {
+                            button {
+                                class: SECONDARY_BUTTON_CLASS,
+                                onclick: {
+                                    let id = project.id_base58();
+                                    move |_| pending_removal.set(Some(id.clone()))
+                                },
+                                "Remove"
+                            }
+                        }
+                    }
+                }
+            }
+
+            if let Some((project, stored)) = editing.read().clone() {
+                ProjectForm {
+                    project,
+                    stored,
+                    on_saved: move |message| {
+                        editing.set(None);
+                        on_saved.call(message);
+                    },
+                    on_cancel: move |_| editing.set(None),
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn ProjectForm(
+    project: Project,
+    stored: bool,
+    on_saved: EventHandler<String>,
+    on_cancel: EventHandler<()>,
+) -> Element {
+    let mut draft = use_signal(|| project.clone());
+    let mut saving = use_signal(|| false);
+    let mut errors = use_signal(Vec::<FieldError>::new);
+
+    use_effect(use_reactive!(|project| {
+        draft.set(project);
+        errors.set(Vec::default());
+    }));
+
+    let submit = move |_| {
+        let project = draft.read().clone();
+
+        let invalid = project.validate().err().unwrap_or_default();
+        errors.set(invalid.clone());
+        if !invalid.is_empty() {
+            return;
+        }
+
+        spawn(async move {
+            saving.set(true);
+            let (path, message) = if stored {
+                ("/project-update", "Project saved")
+            } else {
+                ("/project-create", "Project created")
+            };
+
+            match send_signed::<Project, String>(path, project).await {
+                Ok(_) => on_saved.call(message.to_string()),
+                Err(error) => report_failure(error, errors),
+            }
+            saving.set(false);
+        });
+    };
+
+    let error = move |field: &str| FieldError::message_for(&errors.read(), field);
+
+    rsx! {
+        div { class: "flex flex-col w-full gap-4 mt-5 p-4 rounded-2xl border border-true-blue",
+            h3 { class: "text-xl font-smooch",
+                if stored { "Edit project" } else { "New project" }
+            }
+            TextField { label: "Name", value: draft.read().name.clone(), oninput: move |value| draft.write().name = value, error: error("name") }
+            TextField { label: "Logo URL", value: draft.read().logo.clone(), oninput: move |value| draft.write().logo = value, error: error("logo") }
+            TextField { label: "Icon URL", value: draft.read().icon.clone(), oninput: move |value| draft.write().icon = value, error: error("icon") }
+            TextField { label: "Description", value: draft.read().description.clone(), oninput: move |value| draft.write().description = value, multiline: true, error: error("description") }
+            TextField { label: "Codebase URL", value: draft.read().codebase.clone(), oninput: move |value| draft.write().codebase = value, error: error("codebase") }
+            TextField {
+                label: "Website URL",
+                value: draft.read().website.clone().unwrap_or_default(),
+                oninput: move |value: String| {
+                    draft.write().website = (!value.trim().is_empty()).then_some(value);
+                },
+                error: error("website"),
+            }
+            TextField { label: "Docs URL", value: draft.read().docs.clone(), oninput: move |value| draft.write().docs = value, error: error("docs") }
+            TextField { label: "Package URL", value: draft.read().package_uri.clone(), oninput: move |value| draft.write().package_uri = value, error: error("package_uri") }
+
+            div { class: "flex w-full gap-4",
+                select {
+                    class: "flex bg-true-blue rounded-full py-1 px-1 text-center",
+                    onchange: move |event| {
+                        if let Ok(category) = event.value().parse::<ProjectCategory>() {
+                            draft.write().category = category;
+                        }
+                    },
+                    for category in ProjectCategory::all() {
+                        option {
+                            selected: draft.read().category == *category,
+                            value: category.to_string(),
+                            {category.to_string()}
+                        }
+                    }
+                }
+                select {
+                    class: "flex bg-true-blue rounded-full py-1 px-1 text-center",
+                    onchange: move |event| {
+                        if let Ok(language) = event.value().parse::<CodeLanguage>() {
+                            draft.write().language = language;
+                        }
+                    },
+                    for language in CodeLanguage::all() {
+                        option {
+                            selected: draft.read().language == *language,
+                            value: language.to_string(),
+                            {language.to_string()}
+                        }
+                    }
+                }
+            }
+
+            PhishingEditor {
+                phishing: draft.read().phishing.clone(),
+                onchange: move |phishing| draft.write().phishing = phishing,
+            }
+            for invalid in nested_errors(&errors.read(), "phishing[") {
+                span { class: "text-red-400 text-xs", {invalid.to_string()} }
+            }
+
+            div { class: "flex w-full justify-end gap-2",
+                button { class: SECONDARY_BUTTON_CLASS, onclick: move |_| on_cancel.call(()), "Cancel" }
+                button {
+                    class: BUTTON_CLASS,
+                    disabled: *saving.read(),
+                    onclick: submit,
+                    if *saving.read() { "Saving..." } else { "Save project" }
+                }
+            }
+        }
+    }
+}
+
+/// Adds, edits and removes the phishing warnings of a project draft
+#[component]
+fn PhishingEditor(phishing: Vec<Phishing>, onchange: EventHandler<Vec<Phishing>>) -> Element {
+    let empty = || Phishing {
+        name: String::new(),
+        uri: String::new(),
+        analysis: String::new(),
+        other_uri: Vec::new(),
+    };
+    let mut entry = use_signal(empty);
+    let mut editing = use_signal(|| Option::<usize>::None);
+
+    let listed = phishing.clone();
+    let submit = move |_| {
+        let mut phishing = listed.clone();
+        let warning = entry.read().clone();
+
+        match *editing.read() {
+            Some(index) if index < phishing.len() => phishing[index] = warning,
+            _ => phishing.push(warning),
+        }
+
+        onchange.call(phishing);
+        entry.set(empty());
+        editing.set(None);
+    };
+
+    rsx! {
+        div { class: "flex flex-col w-full gap-2 mt-5",
+            h3 { class: "text-xl font-smooch", "Phishing warnings" }
+            for (index, warning) in phishing.iter().cloned().enumerate() {
+                div { class: "flex w-full items-center justify-between gap-2 text-sm",
+                    span { class: "break-all", {warning.name.as_str()} " - " {warning.uri.as_str()} }
+                    div { class: "flex gap-2",
+                        button {
+                            class: SECONDARY_BUTTON_CLASS,
+                            onclick: move |_| {
+                                entry.set(warning.clone());
+                                editing.set(Some(index));
+                            },
+                            "Edit"
+                        }
+                        button {
+                            class: SECONDARY_BUTTON_CLASS,
+                            onclick: {
+                                let phishing = phishing.clone();
+                                move |_| {
+                                    let mut phishing = phishing.clone();
+                                    phishing.remove(index);
+                                    onchange.call(phishing);
+                                    editing.set(None);
+                                }
+                            },
+                            "Remove"
+                        }
+                    }
+                }
+            }
+            TextField { label: "Impersonating name", value: entry.read().name.clone(), oninput: move |value| entry.write().name = value }
+            TextField { label: "Phishing URL", value: entry.read().uri.clone(), oninput: move |value| entry.write().uri = value }
+            TextField { label: "Analysis", value: entry.read().analysis.clone(), oninput: move |value| entry.write().analysis = value }
+            div { class: "flex w-full justify-end",
+                button {
+                    class: SECONDARY_BUTTON_CLASS,
+                    disabled: entry.read().uri.trim().is_empty(),
+                    onclick: submit,
+                    if editing.read().is_some() { "Update warning" } else { "Add warning" }
+                }
             }
         }
     }
@@ -1,53 +1,157 @@
 use dioxus::prelude::*;
-use trenchesfund_common::{Outcome, Project, REST_ENDPOINT};
+use gloo_timers::callback::Timeout;
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use trenchesfund_common::{
+    CodeLanguage, Project, ProjectCategory, ProjectPage, Publisher, SearchResults, REST_ENDPOINT,
+};
+use wallet_adapter::{wasm_bindgen_futures::spawn_local, web_sys};
 
-use crate::{InstitutionIcon, Loader, NotificationInfo, Route, GLOBAL_MESSAGE};
+use crate::{fetch_api, InstitutionIcon, Loader, NotificationInfo, Route, GLOBAL_MESSAGE};
 
+/// Delay after the last keystroke before a search request is sent
+const SEARCH_DEBOUNCE_MS: u32 = 300;
+
+/// `category` and `language` come from the route query string and hold the
+/// `Display` form of the enum, an empty string means no filter
 #[component]
-pub fn Explore() -> Element {
+pub fn Explore(category: String, language: String) -> Element {
     let mut projects_data = use_signal(|| Option::<Vec<Project>>::default());
+    let mut next_cursor = use_signal(|| Option::<String>::None);
+    let mut category_filter = use_signal(|| category.clone());
+    let mut language_filter = use_signal(|| language.clone());
+    let navigator = use_navigator();
+    let mut search_query = use_signal(String::default);
+    let mut search_results = use_signal(|| Option::<SearchResults>::None);
+    // Replacing the pending timeout drops it, which cancels the previous search
+    let mut search_debounce = use_signal(|| Option::<Timeout>::None);
+
+    let mut on_search = move |query: String| {
+        search_query.set(query.clone());
+
+        if query.trim().is_empty() {
+            search_debounce.set(None);
+            search_results.set(None);
+            return;
+        }
+
+        let timeout = Timeout::new(SEARCH_DEBOUNCE_MS, move || {
+            spawn_local(async move {
+                let endpoint = String::new()
+                    + REST_ENDPOINT
+                    + "/search?q="
+                    + &utf8_percent_encode(&query, NON_ALPHANUMERIC).to_string();
+
+                let outcome =
+                    fetch_api::<SearchResults>(reqwest::Client::new().get(&endpoint)).await;
+
+                // Drop responses for queries the user has already typed past
+                if *search_query.peek() != query {
+                    return;
+                }
+
+                match outcome {
+                    Ok(results) => search_results.set(Some(results)),
+                    Err(error) => GLOBAL_MESSAGE
+                        .write()
+                        .push_back(NotificationInfo::error(error)),
+                }
+            });
+        });
 
-    let send_err_notify = move |msg: &str| {
-        GLOBAL_MESSAGE
-            .write()
-            .push_back(NotificationInfo::error(msg));
-        projects_data.clone().write().replace(Vec::default());
+        search_debounce.set(Some(timeout));
     };
 
-    use_effect(move || {
+    // Keep the filters in step with the route, e.g. on browser back/forward
+    use_effect(use_reactive!(|(category, language)| {
+        if *category_filter.peek() != category {
+            category_filter.set(category);
+        }
+        if *language_filter.peek() != language {
+            language_filter.set(language);
+        }
+    }));
+
+    let mut set_filters = move |category: String, language: String| {
+        category_filter.set(category.clone());
+        language_filter.set(language.clone());
+        navigator.replace(Route::Explore { category, language });
+    };
+
+    let load_page = move |cursor: Option<String>| {
         spawn(async move {
-            let endpoint = String::new() + REST_ENDPOINT + "/projects";
+            let category = category_filter.peek().clone();
+            let language = language_filter.peek().clone();
 
-            if let Ok(response) = reqwest::Client::new().post(&endpoint).send().await {
-                let body = if let Ok(text) = response.text().await {
-                    text
-                } else {
-                    send_err_notify("Unable to get the body text");
-                    return;
-                };
+            let mut params = Vec::<(&str, &str)>::new();
+            if let Some(cursor) = cursor.as_ref() {
+                params.push(("cursor", cursor));
+            }
+            if !category.is_empty() {
+                params.push(("category", &category));
+            }
+            if !language.is_empty() {
+                params.push(("language", &language));
+            }
 
-                if let Ok(data) = serde_json::from_str::<Outcome<Vec<Project>>>(&body) {
-                    match data {
-                        Outcome::Success(success_data) => {
-                            projects_data.write().replace(success_data);
-                        }
-                        Outcome::Failure(failure_data) => {
-                            send_err_notify(&failure_data);
-                        }
+            let mut endpoint = String::new() + REST_ENDPOINT + "/projects";
+            params
+                .iter()
+                .enumerate()
+                .for_each(|(index, (name, value))| {
+                    endpoint.push(if index == 0 { '?' } else { '&' });
+                    endpoint.push_str(name);
+                    endpoint.push('=');
+                    endpoint.extend(utf8_percent_encode(value, NON_ALPHANUMERIC));
+                });
+
+            let outcome = fetch_api::<ProjectPage>(reqwest::Client::new().get(&endpoint)).await;
+
+            // The filters changed while this page was in flight
+            if *category_filter.peek() != category || *language_filter.peek() != language {
+                return;
+            }
+
+            match outcome {
+                Ok(page) => {
+                    if !page.skipped.is_empty() {
+                        web_sys::console::log_1(
+                            &format!("Skipped unreadable projects: {:?}", page.skipped).into(),
+                        );
                     }
-                } else {
-                    send_err_notify(
-                        "Unable to fetch projects! Check your connection and try again",
-                    );
-                    projects_data.write().replace(Vec::default());
+
+                    next_cursor.set(page.next_cursor);
+                    projects_data
+                        .write()
+                        .get_or_insert_with(Vec::default)
+                        .extend(page.projects);
+                }
+                Err(error) => {
+                    GLOBAL_MESSAGE
+                        .write()
+                        .push_back(NotificationInfo::error(error));
+                    projects_data.write().get_or_insert_with(Vec::default);
                 }
-            } else {
-                send_err_notify("Unable to fetch projects! Check your connection and try again");
-                projects_data.write().replace(Vec::default());
             }
         });
+    };
+
+    use_effect(move || {
+        // Subscribe to the filters so the listing restarts when they change
+        let _ = (category_filter.read(), language_filter.read());
+
+        projects_data.set(None);
+        next_cursor.set(None);
+        load_page(Option::None);
     });
 
+    let matches_filters = move |project: &Project| {
+        let category = category_filter.read();
+        let language = language_filter.read();
+
+        (category.is_empty() || project.category.to_string() == *category)
+            && (language.is_empty() || project.language.to_string() == *language)
+    };
+
     rsx! {
         div {id:"explore-top", class: "flex-col justify-around items-center w-full min-h-[100vh] p-5",
             if let Some(projects) = projects_data.read().as_ref() {
@@ -59,66 +163,77 @@ pub fn Explore() -> Element {
                                 select {
                                     class: "flex bg-true-blue rounded-full py-1 px-1 text-center",
                                     id: "language",
-                                    name: "type",
-                                    option { selected: "false", value: "rs", "Rust" }
-                                    option { value: "ts", "TypeScript" }
-                                    option { value: "js", "JavaScript" }
+                                    name: "language",
+                                    onchange: move |event| set_filters(category_filter(), event.value()),
+                                    option { selected: language_filter.read().is_empty(), value: "", "All Languages" }
+                                    for language in CodeLanguage::all() {
+                                        option {
+                                            selected: *language_filter.read() == language.to_string(),
+                                            value: language.to_string(),
+                                            {language.to_string()}
+                                        }
+                                    }
                                 }
                             }
                             div { class: "flex",
                                 select {
                                     class: "flex bg-true-blue rounded-full py-1 px-1 text-center",
-                                    id: "language",
-                                    name: "type",
-                                    option { selected: "false", value: "libraries", "Libraries" }
-                                    option { value: "graphics", "Graphics" }
+                                    id: "category",
+                                    name: "category",
+                                    onchange: move |event| set_filters(event.value(), language_filter()),
+                                    option { selected: category_filter.read().is_empty(), value: "", "All Categories" }
+                                    for category in ProjectCategory::all() {
+                                        option {
+                                            selected: *category_filter.read() == category.to_string(),
+                                            value: category.to_string(),
+                                            {category.to_string()}
+                                        }
+                                    }
                                 }
                             }
                             div { class: "flex",
                                 input {
                                     class: "flex bg-transparent border-true-blue border-b-2 py-1 px-1 text-center text-true-blue placeholder:text-blue-yonder outline-none",
-                                    id: "language",
-                                    name: "type",
-                                    placeholder: "search by organization",
+                                    id: "search",
+                                    name: "search",
+                                    placeholder: "search projects and organizations",
+                                    value: "{search_query}",
+                                    oninput: move |event| on_search(event.value()),
                                 }
                             }
                         }
 
-                    div { class:"w-full h-full gap-8 items-center justify-start flex flex-wrap",
-                        for project in projects.as_slice() {
-                                Link { class: "flex rounded-lg flex-col items-start p-5 w-[300px] hover:bg-blue-700 bg-true-blue rounded-xl",
-                                to: Route::ProjectView{ id: vec![project.name.to_string()]},
-                                div { class:"w-full flex items-center justify-start",
-                                        img { class:"bg-white w-[100px] rounded-xl", src:project.icon.as_str() }
-                                        div { class: "flex p-2 gap-8 mt-5",
-                                                div { class: "flex bg-blue-100 text-blue-800 text-xs font-semibold px-2.5 py-0.5 rounded-full dark:bg-blue-200 dark:text-blue-800",
-                                                        {project.language.to_string()}
-                                                }
-                                                div { class: "flex bg-blue-100 text-blue-800 text-xs font-semibold px-2.5 py-0.5 rounded-full dark:bg-blue-200 dark:text-blue-800",
-                                                        {project.category.to_string()}
-                                                }
-                                        }
-                                    }
-                                    div { class: "flex flex-col w-full",
-                                        div { class: "flex w-full items-start flex-col mt-2.5",
-                                            h5 { class: "text-lg text-wrap flex flex-wrap font-semibold tracking-tight",
-                                                {project.name_short()}
-                                            }
-                                            div { class: "text-black text-lg dark:text-white mt-2 w-full flex items-start justify-between",
-                                                div { class: "flex items-center",
-                                                    span { class: "w-[20px]",
-                                                        {InstitutionIcon()}
-                                                    }
-                                                    span { class: "flex text-sm pl-2",
-                                                        {project.publisher_name()}
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
+                    if let Some(results) = search_results.read().as_ref() {
+                        if results.projects.is_empty() && results.publishers.is_empty() {
+                            div { class: "flex w-full items-center justify-center mt-10",
+                                "No projects or organizations match your search"
+                            }
+                        }
+                        div { class:"w-full h-full gap-8 items-center justify-start flex flex-wrap",
+                            for publisher in results.publishers.iter().cloned() {
+                                PublisherCard { publisher }
+                            }
+                            for project in results.projects.iter().filter(|project| matches_filters(project)).cloned() {
+                                ProjectCard { project }
+                            }
+                        }
+                    } else {
+                        div { class:"w-full h-full gap-8 items-center justify-start flex flex-wrap",
+                            for project in projects.iter().cloned() {
+                                ProjectCard { project }
+                            }
+                        }
+
+                        if let Some(cursor) = next_cursor.read().clone() {
+                            div { class: "flex w-full items-center justify-center mt-10",
+                                button {
+                                    onclick: move |_| load_page(Some(cursor.clone())),
+                                    class: "bg-true-blue hover:bg-blue-900 rounded-full py-2 px-4",
+                                    "Load More"
                                 }
+                            }
                         }
-                     }
+                    }
                 }
             }else {
                 div { class:"w-full flex h-full items-center justify-center",
@@ -128,3 +243,63 @@ pub fn Explore() -> Element {
         }
     }
 }
+
+#[component]
+fn ProjectCard(project: Project) -> Element {
+    rsx! {
+        Link { class: "flex rounded-lg flex-col items-start p-5 w-[300px] hover:bg-blue-700 bg-true-blue rounded-xl",
+            to: Route::ProjectView{ id: vec![project.id_base58()]},
+            div { class:"w-full flex items-center justify-start",
+                img { class:"bg-white w-[100px] rounded-xl", src:project.icon.as_str() }
+                div { class: "flex p-2 gap-8 mt-5",
+                    div { class: "flex bg-blue-100 text-blue-800 text-xs font-semibold px-2.5 py-0.5 rounded-full dark:bg-blue-200 dark:text-blue-800",
+                        {project.language.to_string()}
+                    }
+                    div { class: "flex bg-blue-100 text-blue-800 text-xs font-semibold px-2.5 py-0.5 rounded-full dark:bg-blue-200 dark:text-blue-800",
+                        {project.category.to_string()}
+                    }
+                }
+            }
+            div { class: "flex flex-col w-full",
+                div { class: "flex w-full items-start flex-col mt-2.5",
+                    h5 { class: "text-lg text-wrap flex flex-wrap font-semibold tracking-tight",
+                        {project.name_short()}
+                    }
+                    div { class: "text-black text-lg dark:text-white mt-2 w-full flex items-start justify-between",
+                        div { class: "flex items-center",
+                            span { class: "w-[20px]",
+                                {InstitutionIcon()}
+                            }
+                            span { class: "flex text-sm pl-2",
+                                {project.publisher_name()}
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn PublisherCard(publisher: Publisher) -> Element {
+    rsx! {
+        Link { class: "flex rounded-lg flex-col items-start p-5 w-[300px] hover:bg-blue-700 bg-true-blue rounded-xl",
+            to: Route::OrganizationView{ id: vec![publisher.address()]},
+            div { class:"w-full flex items-center justify-start",
+                img { class:"bg-white w-[100px] rounded-xl", src:publisher.icon.as_str() }
+                div { class: "flex p-2 gap-8 mt-5",
+                    div { class: "flex bg-blue-100 text-blue-800 text-xs font-semibold px-2.5 py-0.5 rounded-full dark:bg-blue-200 dark:text-blue-800",
+                        "Organization"
+                    }
+                }
+            }
+            div { class: "flex w-full items-start flex-col mt-2.5",
+                h5 { class: "text-lg text-wrap flex flex-wrap font-semibold tracking-tight",
+                    {publisher.name.as_str()}
+                }
+                span { class: "flex text-sm mt-2", {publisher.short_address()} }
+            }
+        }
+    }
+}
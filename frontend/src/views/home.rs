@@ -12,8 +12,8 @@ pub fn Home() -> Element {
                 div{class:"text-2xl font-smooch text-center ", "Financing Open Source Maintainers while they Build in the Trenches"}
 
                 div{class:"flex p-4 items-center gap-20",
-                    Link { class:"bg-true-blue sm:text-6xl md:text-2xl font-smooch rounded-full py-2 px-4 mt-20", to: Route::Explore(), "EXPLORE PROJECTS" }
-                    // a { class:"bg-true-blue sm:text-6xl md:text-2xl font-smooch rounded-full py-2 px-4 mt-20", href:"#", "START A COMMUNITY" }
+                    Link { class:"bg-true-blue sm:text-6xl md:text-2xl font-smooch rounded-full py-2 px-4 mt-20", to: Route::Explore { category: String::new(), language: String::new() }, "EXPLORE PROJECTS" }
+                    Link { class:"bg-true-blue sm:text-6xl md:text-2xl font-smooch rounded-full py-2 px-4 mt-20", to: Route::Onboarding(), "START A COMMUNITY" }
                 }
             }
         }
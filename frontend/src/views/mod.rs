@@ -7,6 +7,9 @@ pub use home::*;
 mod explore;
 pub use explore::*;
 
+mod onboarding;
+pub use onboarding::*;
+
 mod organization;
 pub use organization::*;
 
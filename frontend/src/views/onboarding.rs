@@ -0,0 +1,333 @@
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use trenchesfund_common::{
+    validate_address, ApiError, FieldError, Publisher, Validate, REST_ENDPOINT,
+};
+
+use crate::{
+    clear_draft, connected_key, fetch_api, load_draft, save_draft, send_signed, NotificationInfo,
+    Route, TextField, ACTIVE_CONNECTION, BUTTON_CLASS, GLOBAL_MESSAGE, SECONDARY_BUTTON_CLASS,
+};
+
+/// Local storage key of the wizard progress
+const DRAFT_KEY: &str = "trenches-onboarding-draft";
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+enum Step {
+    #[default]
+    Wallet,
+    Profile,
+    Links,
+    Payout,
+    Preview,
+}
+
+impl Step {
+    const ALL: [Self; 5] = [
+        Self::Wallet,
+        Self::Profile,
+        Self::Links,
+        Self::Payout,
+        Self::Preview,
+    ];
+
+    fn title(&self) -> &'static str {
+        match self {
+            Self::Wallet => "Connect wallet",
+            Self::Profile => "Profile",
+            Self::Links => "Links",
+            Self::Payout => "Payout",
+            Self::Preview => "Preview",
+        }
+    }
+
+    fn index(&self) -> usize {
+        Self::ALL
+            .iter()
+            .position(|step| step == self)
+            .unwrap_or_default()
+    }
+
+    fn next(&self) -> Self {
+        Self::ALL
+            .get(self.index() + 1)
+            .copied()
+            .unwrap_or(Self::Preview)
+    }
+
+    /// The publisher fields entered on this step
+    fn fields(&self) -> &'static [&'static str] {
+        match self {
+            Self::Profile => &["name", "description", "icon", "logo"],
+            Self::Links => &["codebase", "website"],
+            Self::Payout => &["mint"],
+            Self::Wallet | Self::Preview => &[],
+        }
+    }
+
+    fn previous(&self) -> Self {
+        self.index()
+            .checked_sub(1)
+            .map(|index| Self::ALL[index])
+            .unwrap_or(Self::Wallet)
+    }
+}
+
+/// What the wizard has collected so far, kept in local storage between steps
+#[derive(Debug, Default, PartialEq, Eq, Clone, Serialize, Deserialize)]
+struct OnboardingDraft {
+    step: Step,
+    name: String,
+    icon: String,
+    logo: String,
+    description: String,
+    codebase: String,
+    website: String,
+    /// The base58 address of the token donations are paid in
+    mint: String,
+}
+
+impl OnboardingDraft {
+    /// The field errors that keep the wizard from leaving `step`
+    fn errors(&self, step: Step) -> Vec<FieldError> {
+        match step {
+            Step::Wallet => connected_key()
+                .err()
+                .map(|error| FieldError::new("wallet", error.message))
+                .into_iter()
+                .collect(),
+            Step::Profile | Step::Links => self
+                .candidate()
+                .validate()
+                .err()
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|error| step.fields().contains(&error.field.as_str()))
+                .collect(),
+            Step::Payout => validate_address("mint", self.mint.trim())
+                .err()
+                .into_iter()
+                .collect(),
+            Step::Preview => [Step::Wallet, Step::Profile, Step::Links, Step::Payout]
+                .into_iter()
+                .flat_map(|step| self.errors(step))
+                .collect(),
+        }
+    }
+
+    fn error(&self, step: Step, field: &str) -> Option<String> {
+        FieldError::message_for(&self.errors(step), field)
+    }
+
+    /// The profile as entered, validated with the same rules the backend applies
+    fn candidate(&self) -> Publisher {
+        let mut publisher = Publisher {
+            name: self.name.trim().to_string(),
+            icon: self.icon.trim().to_string(),
+            logo: self.logo.trim().to_string(),
+            description: self.description.trim().to_string(),
+            codebase: self.codebase.trim().to_string(),
+            website: self.website.trim().to_string(),
+            merch: Vec::default(),
+            ..Publisher::default()
+        };
+
+        if let Ok(public_key) = connected_key() {
+            publisher.public_key = public_key;
+        }
+        if let Some(mint) = Publisher::base58_to_public_key(self.mint.trim()) {
+            publisher.mint = mint;
+        }
+
+        publisher
+    }
+
+    /// The publisher registered for the connected wallet
+    fn publisher(&self) -> Result<Publisher, ApiError> {
+        let errors = self.errors(Step::Preview);
+        if !errors.is_empty() {
+            return Err(ApiError::invalid_fields(errors));
+        }
+
+        Ok(self.candidate())
+    }
+}
+
+#[component]
+pub fn Onboarding() -> Element {
+    let mut draft = use_signal(|| load_draft::<OnboardingDraft>(DRAFT_KEY).unwrap_or_default());
+    // Errors are only shown once the user tried to leave the step
+    let mut show_errors = use_signal(|| false);
+    let mut submitting = use_signal(|| false);
+    let mut registered = use_signal(|| false);
+    let navigator = use_navigator();
+
+    use_effect(move || save_draft(DRAFT_KEY, &*draft.read()));
+
+    let connected_address = ACTIVE_CONNECTION
+        .read()
+        .connected_account()
+        .map(|account| account.address().to_string())
+        .ok();
+
+    // A wallet can only register once, point it to its dashboard instead
+    use_effect(use_reactive!(|connected_address| {
+        registered.set(false);
+
+        if let Some(address) = connected_address {
+            spawn(async move {
+                let endpoint = String::new() + REST_ENDPOINT + "/publisher-info/" + &address;
+
+                if fetch_api::<Publisher>(reqwest::Client::new().post(&endpoint))
+                    .await
+                    .is_ok()
+                {
+                    registered.set(true);
+                }
+            });
+        }
+    }));
+
+    let step = draft.read().step;
+
+    let next = move |_| {
+        if draft.read().errors(step).is_empty() {
+            show_errors.set(false);
+            draft.write().step = step.next();
+        } else {
+            show_errors.set(true);
+        }
+    };
+
+    let submit = move |_| {
+        let publisher = match draft.read().publisher() {
+            Ok(publisher) => publisher,
+            Err(error) => {
+                GLOBAL_MESSAGE
+                    .write()
+                    .push_back(NotificationInfo::error(error));
+                return;
+            }
+        };
+
+        spawn(async move {
+            submitting.set(true);
+            match send_signed::<Publisher, String>("/publishers", publisher).await {
+                Ok(_) => {
+                    clear_draft(DRAFT_KEY);
+                    GLOBAL_MESSAGE
+                        .write()
+                        .push_back(NotificationInfo::new("Welcome to Trenches Fund"));
+                    navigator.push(Route::Dashboard());
+                }
+                Err(error) => {
+                    GLOBAL_MESSAGE
+                        .write()
+                        .push_back(NotificationInfo::error(error));
+                    submitting.set(false);
+                }
+            }
+        });
+    };
+
+    let error = move |field: &str| {
+        if *show_errors.read() {
+            draft.read().error(step, field)
+        } else {
+            None
+        }
+    };
+
+    rsx! {
+        div { class: "flex flex-col justify-start items-center w-full min-h-[100vh] p-5 gap-10",
+            h1 { class: "text-4xl font-smooch mt-8", "Start a Community" }
+
+            ol { class: "flex w-full max-w-screen-md justify-between text-sm",
+                for listed in Step::ALL {
+                    li {
+                        class: if listed == step { "text-white font-bold" } else { "text-blue-200" },
+                        {(listed.index() + 1).to_string()} ". " {listed.title()}
+                    }
+                }
+            }
+
+            section { class: "flex flex-col w-full max-w-screen-md gap-4 p-5 rounded-2xl shadow-soft-dark",
+                {match step {
+                    Step::Wallet => rsx! {
+                        if let Some(address) = connected_address.as_ref() {
+                            p { class: "break-all", "Registering " {address.as_str()} }
+                            if *registered.read() {
+                                p { class: "text-blue-200 text-sm", "This wallet is already a publisher." }
+                                Link { class: BUTTON_CLASS, to: Route::Dashboard(), "Open the dashboard" }
+                            }
+                        } else {
+                            p { "Connect the wallet that will own your publisher profile using the button at the top of the page." }
+                        }
+                        if let Some(error) = error("wallet") {
+                            span { class: "text-red-400 text-xs", {error} }
+                        }
+                    },
+                    Step::Profile => rsx! {
+                        TextField { label: "Name", value: draft.read().name.clone(), oninput: move |value| draft.write().name = value, error: error("name") }
+                        TextField { label: "Description", value: draft.read().description.clone(), oninput: move |value| draft.write().description = value, multiline: true, error: error("description") }
+                        TextField { label: "Icon URL", value: draft.read().icon.clone(), oninput: move |value| draft.write().icon = value, error: error("icon") }
+                        TextField { label: "Logo URL", value: draft.read().logo.clone(), oninput: move |value| draft.write().logo = value, error: error("logo") }
+                    },
+                    Step::Links => rsx! {
+                        TextField { label: "Codebase URL", value: draft.read().codebase.clone(), oninput: move |value| draft.write().codebase = value, error: error("codebase") }
+                        TextField { label: "Website URL", value: draft.read().website.clone(), oninput: move |value| draft.write().website = value, error: error("website") }
+                    },
+                    Step::Payout => rsx! {
+                        p { class: "text-blue-200 text-sm", "Donations to your community are paid in this token." }
+                        TextField { label: "Token mint address", value: draft.read().mint.clone(), oninput: move |value| draft.write().mint = value, error: error("mint") }
+                    },
+                    Step::Preview => rsx! {
+                        div { class: "flex flex-col w-full items-center gap-4 text-center",
+                            if !draft.read().icon.is_empty() {
+                                img { class: "w-[100px] rounded-xl", src: draft.read().icon.clone() }
+                            }
+                            h2 { class: "text-3xl break-all", {draft.read().name.clone()} }
+                            p { class: "text-sm", {draft.read().description.clone()} }
+                            p { class: "text-sm break-all", "Codebase: " {draft.read().codebase.clone()} }
+                            p { class: "text-sm break-all", "Website: " {draft.read().website.clone()} }
+                            p { class: "text-sm break-all", "Mint: " {draft.read().mint.clone()} }
+                        }
+                        for error in draft.read().errors(Step::Preview) {
+                            span { class: "text-red-400 text-xs", {error.to_string()} }
+                        }
+                    },
+                }}
+
+                div { class: "flex w-full justify-between mt-5",
+                    if step != Step::Wallet {
+                        button {
+                            class: SECONDARY_BUTTON_CLASS,
+                            onclick: move |_| {
+                                show_errors.set(false);
+                                draft.write().step = step.previous();
+                            },
+                            "Back"
+                        }
+                    } else {
+                        span {}
+                    }
+                    if step == Step::Preview {
+                        button {
+                            class: BUTTON_CLASS,
+                            disabled: *submitting.read() || !draft.read().errors(Step::Preview).is_empty(),
+                            onclick: submit,
+                            if *submitting.read() { "Registering..." } else { "Sign and register" }
+                        }
+                    } else {
+                        button {
+                            class: BUTTON_CLASS,
+                            disabled: step == Step::Wallet && *registered.read(),
+                            onclick: next,
+                            "Next"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,70 +1,73 @@
 use dioxus::prelude::*;
-use solana_payments::SolanaPayUrl;
-use trenchesfund_common::{Outcome, Publisher, REST_ENDPOINT};
+use gloo_timers::future::TimeoutFuture;
+use trenchesfund_common::{
+    format_amount, parse_amount, ApiError, DonationRequest, ErrorCode, Publisher,
+    PublisherRevision, Supporter, TokenTotal, TransferRequest, TransferStatus, NATIVE_MINT,
+    REST_ENDPOINT,
+};
 use wallet_adapter::web_sys;
 
-use crate::{utils::copied_address, CopySvg, Loader, NotificationInfo, ReceiveSvg, GLOBAL_MESSAGE};
+use crate::{
+    fetch_api, format_date, start_of_month, utils::copied_address, CopySvg, LoadFailed, Loader,
+    NotificationInfo, ReceiveSvg, TextField, BUTTON_CLASS, GLOBAL_MESSAGE, SECONDARY_BUTTON_CLASS,
+};
+
+/// Milliseconds between two checks of a transfer request waiting for its payment
+const TRANSFER_POLL_MILLIS: u32 = 5_000;
+
+/// How many of the top supporters a publisher page lists
+const TOP_SUPPORTERS: usize = 5;
 
 #[component]
 pub fn OrganizationView(id: Vec<String>) -> Element {
     let mut publisher_info = use_signal(|| Option::<Publisher>::default());
     let mut show_receive_modal = use_signal(|| false);
     let mut mint_address = use_signal(|| String::default());
-
-    // web_sys::console::log_1(&"EXEC 1".into());
+    let mut load_error = use_signal(|| Option::<ApiError>::None);
+    let mut history = use_signal(Vec::<PublisherRevision>::new);
 
     let id = id.first().cloned();
 
-    let send_err_notify = move |msg: &str| {
-        GLOBAL_MESSAGE
-            .write()
-            .push_back(NotificationInfo::error(msg));
-        publisher_info.clone().write().take();
-    };
-
     use_effect(move || {
         if let Some(id_valid) = id.as_ref().cloned() {
             spawn(async move {
                 let endpoint =
                     String::new() + REST_ENDPOINT + "/publisher-info/" + id_valid.as_str();
 
-                if let Ok(response) = reqwest::Client::new().post(&endpoint).send().await {
-                    let body = if let Ok(text) = response.text().await {
-                        text
-                    } else {
-                        send_err_notify("Unable to get the body text");
-                        return;
-                    };
-
-                    if let Ok(data) = serde_json::from_str::<Outcome<Option<Publisher>>>(&body) {
-                        match data {
-                            Outcome::Success(success_data) => {
-                                let success_data = success_data.unwrap();
-
-                                *mint_address.write() = success_data.mint_address().clone();
+                match fetch_api::<Publisher>(reqwest::Client::new().post(&endpoint)).await {
+                    Ok(publisher) => {
+                        *mint_address.write() = publisher.mint_address().clone();
+                        publisher_info.write().replace(publisher);
 
-                                publisher_info.write().replace(success_data);
-                            }
-                            Outcome::Failure(failure_data) => {
-                                send_err_notify(&failure_data);
-                            }
+                        let endpoint = endpoint + "/history";
+                        match fetch_api::<Vec<PublisherRevision>>(
+                            reqwest::Client::new().get(&endpoint),
+                        )
+                        .await
+                        {
+                            Ok(revisions) => *history.write() = revisions,
+                            Err(error) => web_sys::console::log_1(
+                                &format!("Unable to load the profile history: {}", error.message)
+                                    .into(),
+                            ),
                         }
-                    } else {
-                        send_err_notify(
-                            "Unable to fetch publisher! Check your connection and try again",
-                        );
-                        publisher_info.write().take();
                     }
-                } else {
-                    send_err_notify(
-                        "Unable to fetch publisher! Check your connection and try again",
-                    );
-                    publisher_info.write().take();
+                    Err(error) => {
+                        if error.code != ErrorCode::NotFound {
+                            GLOBAL_MESSAGE
+                                .write()
+                                .push_back(NotificationInfo::error(&error));
+                        }
+                        load_error.write().replace(error);
+                    }
                 }
             });
         } else {
             web_sys::console::log_1(&"INVALID ID".into());
-            send_err_notify("The route received an invalid id");
+            load_error.write().replace(ApiError::new(
+                ErrorCode::NotFound,
+                "The route received an invalid id",
+            ));
         }
     });
 
@@ -154,6 +157,8 @@ pub fn OrganizationView(id: Vec<String>) -> Element {
                         }
                     }
                 }
+
+                FundingSummary { publisher: publisher.address() }
                 div {
                     class: "flex flex-col w-full min-h-screen p-5 items-center justify-center gap-12",
                     id: "sponsor",
@@ -239,8 +244,12 @@ pub fn OrganizationView(id: Vec<String>) -> Element {
                     }
                 }
 
+                ProfileHistory { revisions: history.read().clone() }
+
                 ReceiveTokens{show_receive_modal, publisher: publisher.clone()}
 
+        } else if let Some(error) = load_error.read().as_ref() {
+            LoadFailed { error: error.clone() }
         } else {
                 {Loader()}
 
@@ -248,25 +257,224 @@ pub fn OrganizationView(id: Vec<String>) -> Element {
     }
 }
 
+/// The recorded edits of a publisher profile, newest first
 #[component]
-pub fn ReceiveTokens(show_receive_modal: Signal<bool>, publisher: Publisher) -> Element {
-    let solana_pay_url = SolanaPayUrl::default()
-        .add_recipient(&publisher.address())
-        .unwrap_or_default()
-        .add_label(&publisher.name)
-        .unwrap_or_default()
-        .add_message("Thanks for buying coffee and keeping the lights on.")
-        .unwrap_or_default()
-        .to_url();
-
-    let qrcode = if let Ok(qr) = crate::address_qrcode(&solana_pay_url) {
-        qr
+pub fn ProfileHistory(revisions: Vec<PublisherRevision>) -> Element {
+    let mut edits = revisions
+        .iter()
+        .enumerate()
+        .map(|(index, revision)| {
+            let date = revision
+                .edited_at
+                .map(format_date)
+                .unwrap_or("Before history was kept".to_string());
+            let signer = Publisher::public_key_to_base58(&revision.signer);
+            let changes = match index.checked_sub(1).map(|previous| &revisions[previous]) {
+                Some(previous) => {
+                    let fields = revision.publisher.changed_fields(&previous.publisher);
+
+                    if fields.is_empty() {
+                        "No changes".to_string()
+                    } else {
+                        "Changed ".to_string() + &fields.join(", ")
+                    }
+                }
+                None => "Profile created".to_string(),
+            };
+
+            (date, signer, changes)
+        })
+        .collect::<Vec<(String, String, String)>>();
+    edits.reverse();
+
+    if edits.is_empty() {
+        return rsx! {};
+    }
+
+    rsx! {
+        div {
+            class: "flex flex-col w-full p-5 mb-20 items-center justify-center",
+            id: "history",
+            div { class: "mb-10 flex w-full items-center justify-center text-center",
+                span { class: "flex w-[30px] mr-1",
+                    img { alt: "", src: "/images/icon.svg", srcset: "" }
+                }
+                h1 { class: "flex text-3xl", "Profile History" }
+            }
+            div { class: "flex flex-col w-full max-w-screen-md gap-4",
+                for (date, signer, changes) in edits {
+                    div { class: "flex flex-col w-full rounded-2xl p-4 bg-true-blue text-sm text-white",
+                        div { class: "flex w-full justify-between flex-wrap gap-2",
+                            span { class: "text-lg font-smooch", {date} }
+                            span { {changes} }
+                        }
+                        div { class: "flex w-full mt-2 text-xs text-wrap break-all",
+                            "Signed by: " {signer}
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Names a mint as SOL or by its shortened address
+fn token_name(mint: &str) -> String {
+    if mint == NATIVE_MINT {
+        "SOL".to_string()
     } else {
-        rsx! {
-            div { class:"text-black dark:text-white", }
+        Publisher::shorten(mint)
+    }
+}
+
+/// What a publisher raised this month and its largest supporters ever
+#[component]
+pub fn FundingSummary(publisher: String) -> Element {
+    let mut raised = use_signal(Vec::<TokenTotal>::new);
+    let mut supporters = use_signal(Vec::<Supporter>::new);
+
+    use_effect(move || {
+        let publisher = publisher.clone();
+
+        spawn(async move {
+            let endpoint = String::new()
+                + REST_ENDPOINT
+                + "/donations/totals?publisher="
+                + &publisher
+                + "&from="
+                + &start_of_month().to_string();
+            match fetch_api::<Vec<TokenTotal>>(reqwest::Client::new().get(&endpoint)).await {
+                Ok(totals) => *raised.write() = totals,
+                Err(error) => web_sys::console::log_1(
+                    &format!("Unable to load the donation totals: {}", error.message).into(),
+                ),
+            }
+
+            let endpoint = String::new()
+                + REST_ENDPOINT
+                + "/donations/supporters?publisher="
+                + &publisher
+                + "&limit="
+                + &TOP_SUPPORTERS.to_string();
+            match fetch_api::<Vec<Supporter>>(reqwest::Client::new().get(&endpoint)).await {
+                Ok(top) => *supporters.write() = top,
+                Err(error) => web_sys::console::log_1(
+                    &format!("Unable to load the top supporters: {}", error.message).into(),
+                ),
+            }
+        });
+    });
+
+    rsx! {
+        div {
+            class: "flex flex-col w-full p-5 items-center justify-center gap-8",
+            id: "funding",
+            div { class: "flex flex-col w-full max-w-screen-md rounded-2xl p-4 bg-true-blue text-white items-center",
+                h1 { class: "text-2xl font-smooch", "Raised this month" }
+                if raised.read().is_empty() {
+                    span { class: "text-sm mt-2", "No donations yet this month" }
+                }
+                for total in raised.read().iter() {
+                    div { class: "flex w-full justify-between mt-2",
+                        span { class: "text-lg", {format_amount(total.amount)} " " {token_name(&total.mint)} }
+                        span { class: "text-sm", {total.donations.to_string()} " donations" }
+                    }
+                }
+            }
+            if !supporters.read().is_empty() {
+                div { class: "flex flex-col w-full max-w-screen-md rounded-2xl p-4 bg-true-blue text-white items-center",
+                    h1 { class: "text-2xl font-smooch", "Top supporters" }
+                    for supporter in supporters.read().iter() {
+                        div { class: "flex w-full justify-between mt-2 text-sm",
+                            span { class: "break-all", {Publisher::shorten(&supporter.donor)} }
+                            span { {format_amount(supporter.amount)} " " {token_name(&supporter.mint)} }
+                        }
+                    }
+                }
+            }
         }
+    }
+}
+
+#[component]
+pub fn ReceiveTokens(show_receive_modal: Signal<bool>, publisher: Publisher) -> Element {
+    let mut amount = use_signal(String::new);
+    let mut transfer = use_signal(|| Option::<TransferRequest>::None);
+    let mut requesting = use_signal(|| false);
+
+    let recipient = publisher.address();
+    // Every tip gets its own transfer request so that its payment can be found on chain
+    let request_transfer = move |_| {
+        let Some(amount) = parse_amount(&amount.read()) else {
+            GLOBAL_MESSAGE
+                .write()
+                .push_back(NotificationInfo::error("Enter an amount like 1.5"));
+            return;
+        };
+        let body = DonationRequest {
+            publisher: recipient.clone(),
+            project: None,
+            amount,
+        };
+
+        spawn(async move {
+            requesting.set(true);
+            let request = reqwest::Client::new()
+                .post(String::new() + REST_ENDPOINT + "/transfer-request")
+                .header("Content-Type", "application/json")
+                .body(serde_json::to_string(&body).unwrap_or_default());
+            let created = fetch_api::<TransferRequest>(request).await;
+            requesting.set(false);
+
+            let reference = match created {
+                Ok(created) => {
+                    let reference = created.reference.clone();
+                    transfer.set(Some(created));
+                    reference
+                }
+                Err(error) => {
+                    GLOBAL_MESSAGE
+                        .write()
+                        .push_back(NotificationInfo::error(error));
+                    return;
+                }
+            };
+
+            let endpoint = String::new() + REST_ENDPOINT + "/transfer-request/" + &reference;
+            loop {
+                TimeoutFuture::new(TRANSFER_POLL_MILLIS).await;
+
+                // Stop once the modal is closed or another request replaced this one
+                let watched = transfer
+                    .peek()
+                    .as_ref()
+                    .is_some_and(|transfer| transfer.reference == reference);
+                if !*show_receive_modal.peek() || !watched {
+                    break;
+                }
+
+                if let Ok(polled) =
+                    fetch_api::<TransferRequest>(reqwest::Client::new().get(&endpoint)).await
+                {
+                    let confirmed = polled.is_confirmed();
+                    transfer.set(Some(polled));
+
+                    if confirmed {
+                        break;
+                    }
+                }
+            }
+        });
     };
 
+    let qrcode = transfer.read().as_ref().map(|transfer| {
+        crate::address_qrcode(&transfer.url()).unwrap_or_else(|_| {
+            rsx! {
+                div { class:"text-black dark:text-white", }
+            }
+        })
+    });
+
     let address_inner = publisher.address().clone();
 
     if *show_receive_modal.read() {
@@ -312,15 +520,42 @@ pub fn ReceiveTokens(show_receive_modal: Signal<bool>, publisher: Publisher) ->
                                 span { {publisher.short_address()} }
                             }
                         }
-                        div{class:"w-[200px] rounded-xl flex flex-col mt-5 mb-5 bg-white",
-                            div{class:"w-full flex items-center text-center justify-center",
-                                {qrcode}
+                        if let (Some(current), Some(qrcode)) = (transfer.read().clone(), qrcode) {
+                            div{class:"w-[200px] rounded-xl flex flex-col mt-5 mb-5 bg-white",
+                                div{class:"w-full flex items-center text-center justify-center",
+                                    {qrcode}
+                                }
                             }
-                        }
-
                             div {class:"w-full flex items-center text-center justify-center font-smooch text-xl",
-                                "Solana Pay QR Code"
+                                "Scan to send " {format_amount(current.amount)} " tokens"
+                            }
+                            match current.status {
+                                TransferStatus::Pending => rsx! {
+                                    p { class: "text-sm mt-2", "Waiting for the payment..." }
+                                },
+                                TransferStatus::Confirmed { signature, .. } => rsx! {
+                                    p { class: "text-sm mt-2", "Donation confirmed, thank you!" }
+                                    p { class: "text-xs break-all px-5", "Transaction: " {signature} }
+                                },
                             }
+                            div { class: "flex mt-5",
+                                button {
+                                    class: SECONDARY_BUTTON_CLASS,
+                                    onclick: move |_| transfer.set(None),
+                                    "New donation"
+                                }
+                            }
+                        } else {
+                            div { class: "flex flex-col w-[80%] gap-4 mt-5",
+                                TextField { label: "Amount", value: amount.read().clone(), oninput: move |value| amount.set(value) }
+                                button {
+                                    class: BUTTON_CLASS,
+                                    disabled: *requesting.read(),
+                                    onclick: request_transfer,
+                                    if *requesting.read() { "Creating..." } else { "Create Solana Pay request" }
+                                }
+                            }
+                        }
                     }
                 }
             }
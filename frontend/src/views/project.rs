@@ -1,63 +1,45 @@
 use dioxus::prelude::*;
-use trenchesfund_common::{Outcome, Project, REST_ENDPOINT};
+use trenchesfund_common::{ApiError, ErrorCode, Project, REST_ENDPOINT};
 use wallet_adapter::web_sys;
 
 use crate::{
-    CodebaseSvg, DocsSvg, InternetSvg, Loader, NotificationInfo, PackageSvg, PhishingSvg, Route,
-    SolanaFoundationLogoSvg, GLOBAL_MESSAGE,
+    fetch_api, CodebaseSvg, DocsSvg, InternetSvg, Loader, NotificationInfo, PackageSvg,
+    PhishingSvg, Route, SolanaFoundationLogoSvg, GLOBAL_MESSAGE,
 };
 
 #[component]
 pub fn ProjectView(id: Vec<String>) -> Element {
     let mut project_info = use_signal(|| Option::<Project>::None);
+    let mut load_error = use_signal(|| Option::<ApiError>::None);
 
-    let send_err_notify = move |msg: &str| {
-        GLOBAL_MESSAGE
-            .write()
-            .push_back(NotificationInfo::error(msg));
-        project_info.clone().write().take();
-    };
-
-    let id = id.first().cloned();
+    // Either the project id or the publisher address followed by the project slug
+    let id = (!id.is_empty() && id.len() <= 2).then(|| id.join("/"));
 
     use_effect(move || {
         if let Some(id_valid) = id.as_ref().cloned() {
             spawn(async move {
                 let endpoint = String::new() + REST_ENDPOINT + "/project-info/" + id_valid.as_str();
 
-                if let Ok(response) = reqwest::Client::new().post(&endpoint).send().await {
-                    let body = if let Ok(text) = response.text().await {
-                        text
-                    } else {
-                        send_err_notify("Unable to get the body text");
-                        return;
-                    };
-
-                    if let Ok(data) = serde_json::from_str::<Outcome<Option<Project>>>(&body) {
-                        match data {
-                            Outcome::Success(success_data) => {
-                                project_info.write().replace(success_data.unwrap());
-                            }
-                            Outcome::Failure(failure_data) => {
-                                send_err_notify(&failure_data);
-                            }
+                match fetch_api::<Project>(reqwest::Client::new().post(&endpoint)).await {
+                    Ok(project) => {
+                        project_info.write().replace(project);
+                    }
+                    Err(error) => {
+                        if error.code != ErrorCode::NotFound {
+                            GLOBAL_MESSAGE
+                                .write()
+                                .push_back(NotificationInfo::error(&error));
                         }
-                    } else {
-                        send_err_notify(
-                            "Unable to fetch projects! Check your connection and try again",
-                        );
-                        project_info.write().take();
+                        load_error.write().replace(error);
                     }
-                } else {
-                    send_err_notify(
-                        "Unable to fetch projects! Check your connection and try again",
-                    );
-                    project_info.write().take();
                 }
             });
         } else {
             web_sys::console::log_1(&"INVALID ID".into());
-            send_err_notify("The route received an invalid id");
+            load_error.write().replace(ApiError::new(
+                ErrorCode::NotFound,
+                "The route received an invalid id",
+            ));
         }
     });
 
@@ -196,8 +178,27 @@ pub fn ProjectView(id: Vec<String>) -> Element {
                     }
                 }
             }
-        else {
+        else if let Some(error) = load_error.read().as_ref() {
+            LoadFailed { error: error.clone() }
+        } else {
             {Loader()}
         }
     }
 }
+
+#[component]
+pub fn LoadFailed(error: ApiError) -> Element {
+    let title = if error.code == ErrorCode::NotFound {
+        "Not Found"
+    } else {
+        "Something went wrong"
+    };
+
+    rsx! {
+        div { class: "flex flex-col w-full min-h-[50vh] items-center justify-center gap-4",
+            h1 { class: "text-3xl font-smooch", {title} }
+            p { class: "text-blue-200 text-sm", {error.message.as_str()} }
+            Link { class: "bg-true-blue rounded-full py-2 px-4", to: Route::Explore { category: String::new(), language: String::new() }, "Explore Projects" }
+        }
+    }
+}